@@ -3,9 +3,9 @@ use ratatui::Frame;
 
 
 use crate::{
-    config::Config, context::AppContext, ui::states::{
-        network_information::NetworkInformationState, order_information::OrderDashboardState,
-        swap_information::SwapDashboardState, State, StateType,
+    config::Config, context::AppContext, keystore::Wallet, ui::states::{
+        network_information::NetworkInformationState, order_history::OrderHistoryState,
+        order_information::OrderDashboardState, resume_swap::ResumeSwapState, swap_information::SwapDashboardState, State, StateType,
     }
 };
 
@@ -17,14 +17,22 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(network: &str, config: Config) -> App {
-        let context = AppContext::new(network, &config.get_network(network).unwrap());
+    pub fn new(network: &str, config: Config, wallet: Wallet) -> anyhow::Result<App> {
+        let context = AppContext::new(network, config.get_network(network)?, wallet)?;
         
-        App {
-            context: context.clone(),
-            state: Box::new(NetworkInformationState::new(context.api.quote.strategies_map)),
+        // offer to pick up any swap that was still in flight when the TUI last exited
+        let unfinished = context.journal.unfinished().unwrap_or_default();
+        let state: Box<dyn State> = if unfinished.is_empty() {
+            Box::new(NetworkInformationState::new(context.api.quote.strategies_map.clone()))
+        } else {
+            Box::new(ResumeSwapState::new(unfinished))
+        };
+        
+        Ok(App {
+            context,
+            state,
             should_quit: false,
-        }
+        })
     }
 
     pub fn draw(&mut self, frame: &mut Frame) {
@@ -33,7 +41,15 @@ impl App {
 
     pub fn handle_key(&mut self, key: KeyEvent) {
         let next_state = self.state.handle_key(key, &mut self.context);
+        self.transition(next_state);
+    }
+
+    pub fn tick(&mut self) {
+        let next_state = self.state.tick(&mut self.context);
+        self.transition(next_state);
+    }
 
+    fn transition(&mut self, next_state: Option<StateType>) {
         if let Some(state_type) = next_state {
             match state_type {
                 StateType::NetworkInformation => {
@@ -45,6 +61,15 @@ impl App {
                 StateType::OrderInformation => {
                     self.state = Box::new(OrderDashboardState::new());
                 }
+                StateType::ResumeOrder(entry) => {
+                    self.state = Box::new(OrderDashboardState::resume(*entry));
+                }
+                StateType::OrderHistory => {
+                    self.state = Box::new(OrderHistoryState::new(&self.context));
+                }
+                StateType::OpenOrder(matched_order) => {
+                    self.state = Box::new(OrderDashboardState::open(*matched_order));
+                }
                 StateType::Quit => {
                     self.should_quit = true;
                 }
@@ -0,0 +1,667 @@
+use std::fmt::Display;
+use std::thread::sleep;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+
+use crate::context::AppContext;
+use crate::journal::JournalEntry;
+use crate::keystore::{KeySource, WalletKeys};
+use crate::service::garden::error::GardenError;
+use crate::service::garden::types::{parse_quote, MatchedOrder, SingleSwap, Strategy};
+use crate::service::signer::WalletSigner;
+use crate::swap::{
+    build_order, create_order, export_psbt, import_psbt, infer_progress, initiate_source, parse_secret, poll_destination,
+    recover_secret, redeem_destination, refund_source, secret_matches, exceeded_step_timeout, OrderProgress, PsbtAction, StepOutcome,
+    POLL_INTERVAL,
+};
+
+/// Process exit codes of the headless subcommands.
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    /// Network, API or chain errors.
+    pub const FAILURE: i32 = 1;
+    /// Invalid arguments, same as clap's own usage errors.
+    pub const USAGE: i32 = 2;
+    /// Unknown strategy, order or journal entry.
+    pub const NOT_FOUND: i32 = 3;
+    /// A swap step did not finish within its timeout.
+    pub const TIMEOUT: i32 = 4;
+}
+
+pub struct CliError {
+    code: i32,
+    error: anyhow::Error,
+}
+
+impl CliError {
+    fn new(code: i32, error: impl Display) -> Self {
+        CliError {
+            code,
+            error: anyhow!("{}", error),
+        }
+    }
+}
+
+fn garden_exit_code(error: &GardenError) -> i32 {
+    match error {
+        GardenError::UnknownStrategy(_) | GardenError::OrderNotFound(_) => exit_code::NOT_FOUND,
+        _ => exit_code::FAILURE,
+    }
+}
+
+impl From<GardenError> for CliError {
+    fn from(error: GardenError) -> Self {
+        CliError {
+            code: garden_exit_code(&error),
+            error: error.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for CliError {
+    // swap steps wrap orderbook errors in context, an unknown order is still not found
+    fn from(error: anyhow::Error) -> Self {
+        CliError {
+            code: error.downcast_ref::<GardenError>().map_or(exit_code::FAILURE, garden_exit_code),
+            error,
+        }
+    }
+}
+
+/// Writes results to stdout, either as text or as a single JSON document.
+/// In text mode progress of long running commands goes to stderr.
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn emit(&self, human: String, value: Value) {
+        if self.json {
+            println!("{}", value);
+        } else {
+            println!("{}", human);
+        }
+    }
+
+    fn progress(&self, message: &str) {
+        if !self.json {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+pub fn subcommands() -> Vec<Command> {
+    let order_id = Arg::new("order_id")
+        .value_name("ORDER_ID")
+        .help("id of the order in the orderbook")
+        .required(true);
+    let strategy = Arg::new("strategy")
+        .short('s')
+        .long("strategy")
+        .value_name("STRATEGY_ID")
+        .help("id of the strategy as listed by `strategies`")
+        .required(true);
+    let amount = Arg::new("amount")
+        .short('a')
+        .long("amount")
+        .value_name("AMOUNT")
+        .help("source amount in base units")
+        .required(true);
+    let action = Arg::new("action")
+        .long("action")
+        .value_name("ACTION")
+        .help("bitcoin transaction of the order")
+        .value_parser(["initiate", "redeem", "refund"])
+        .required(true);
+
+    vec![
+        Command::new("strategies").about("list the strategies offered on the network"),
+        Command::new("quote")
+            .about("get the price of every solver quoting the strategy's pair for a source amount")
+            .args([strategy.clone(), amount.clone()]),
+        Command::new("swap")
+            .about("create, initiate and redeem a swap end to end")
+            .args([strategy, amount]),
+        Command::new("status")
+            .about("show the source and destination state of an order")
+            .arg(order_id.clone()),
+        Command::new("redeem")
+            .about("redeem the destination of an order using the secret from the journal")
+            .arg(order_id.clone()),
+        Command::new("refund")
+            .about("refund the source of an order after its timelock expired")
+            .args([
+                order_id.clone(),
+                Arg::new("counterparty_sig")
+                    .long("counterparty-sig")
+                    .value_name("SIGNATURE")
+                    .help("redeemer's hex signature to refund an EVM swap through instantRefund before the timelock"),
+            ]),
+        Command::new("secret")
+            .about("move the secret of an order between machines")
+            .subcommand_required(true)
+            .subcommands([
+                Command::new("export")
+                    .about("print the secret of an order from the journal or its derivation")
+                    .arg(order_id.clone()),
+                Command::new("import")
+                    .about("check a secret against an order and save it to the journal")
+                    .args([
+                        order_id.clone(),
+                        Arg::new("secret")
+                            .value_name("SECRET")
+                            .help("the order's secret in hex")
+                            .required(true),
+                    ]),
+            ]),
+        Command::new("psbt")
+            .about("sign an order's bitcoin transactions with an external wallet")
+            .subcommand_required(true)
+            .subcommands([
+                Command::new("export")
+                    .about("print the unsigned transaction as a base64 PSBT")
+                    .args([order_id.clone(), action.clone()]),
+                Command::new("import")
+                    .about("finalize a signed PSBT and broadcast it")
+                    .args([
+                        order_id.clone(),
+                        action,
+                        Arg::new("psbt")
+                            .value_name("PSBT")
+                            .help("the signed PSBT in base64")
+                            .required(true),
+                    ]),
+                Command::new("unlock")
+                    .about("release the UTXOs of an exported funding PSBT that will not be broadcast")
+                    .arg(order_id),
+            ]),
+        Command::new("signer-daemon")
+            .about("serve the wallet's keys to --signer-socket clients, a stand-in for a real signer")
+            .arg(
+                Arg::new("socket")
+                    .long("socket")
+                    .value_name("PATH")
+                    .help("Unix socket to listen on")
+                    .required(true),
+            ),
+    ]
+}
+
+/// Runs a headless subcommand and returns the process exit code.
+pub fn run(name: &str, matches: &ArgMatches, context: AppContext, json: bool) -> i32 {
+    let output = Output { json };
+
+    let result = match name {
+        "strategies" => strategies(&context, &output),
+        "quote" => quote(&context, matches, &output),
+        "swap" => swap(context, matches, &output),
+        "status" => status(&context, matches, &output),
+        "redeem" => redeem(context, matches, &output),
+        "refund" => refund(&context, matches, &output),
+        "secret" => match matches.subcommand() {
+            Some(("export", matches)) => export_secret(&context, matches, &output),
+            Some(("import", matches)) => import_secret(&context, matches, &output),
+            _ => Err(CliError::new(exit_code::USAGE, "expected `secret export` or `secret import`")),
+        },
+        "psbt" => match matches.subcommand() {
+            Some(("export", matches)) => export_psbt_cmd(context, matches, &output),
+            Some(("import", matches)) => import_psbt_cmd(context, matches, &output),
+            Some(("unlock", matches)) => unlock_psbt_cmd(&context, matches, &output),
+            _ => Err(CliError::new(exit_code::USAGE, "expected `psbt export`, `psbt import` or `psbt unlock`")),
+        },
+        _ => Err(CliError::new(exit_code::USAGE, format!("unknown subcommand {}", name))),
+    };
+
+    match result {
+        Ok(()) => exit_code::SUCCESS,
+        Err(e) => report(e, json),
+    }
+}
+
+/// Prints a subcommand's error and returns its exit code. Also used when the context
+/// cannot be set up, e.g. the orderbook is unreachable or rejects the sign-in.
+pub fn report(error: CliError, json: bool) -> i32 {
+    if json {
+        println!("{}", json!({ "error": format!("{:#}", error.error), "code": error.code }));
+    } else {
+        eprintln!("error: {:#}", error.error);
+    }
+    error.code
+}
+
+/// Serves the local keys until the process is stopped. Runs without an `AppContext`, so the
+/// daemon neither authenticates with the orderbook nor needs one to be reachable.
+pub fn signer_daemon(matches: &ArgMatches, key_source: &KeySource) -> i32 {
+    let socket = matches
+        .get_one::<String>("socket")
+        .expect("socket is required");
+
+    let result = WalletKeys::load(key_source).and_then(|keys| {
+        let signer = keys.into_signer();
+        eprintln!("Signing for {} on {}", signer.address(), socket);
+        serve_signer(socket, &signer)
+    });
+
+    match result {
+        Ok(()) => exit_code::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            exit_code::FAILURE
+        }
+    }
+}
+
+#[cfg(unix)]
+fn serve_signer(socket: &str, signer: &dyn WalletSigner) -> anyhow::Result<()> {
+    crate::service::signer::remote::serve(std::path::Path::new(socket), signer)
+}
+
+#[cfg(not(unix))]
+fn serve_signer(_socket: &str, _signer: &dyn WalletSigner) -> anyhow::Result<()> {
+    Err(anyhow!("the signer daemon needs Unix sockets"))
+}
+
+fn strategies(context: &AppContext, output: &Output) -> Result<(), CliError> {
+    let mut strategies: Vec<&Strategy> = context.api.quote.strategies_map.values().collect();
+    strategies.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let human = strategies
+        .iter()
+        .map(|strategy| {
+            format!(
+                "{}  {}  min {} max {} fee {} bips",
+                strategy.id,
+                strategy.order_pair(),
+                strategy.min_amount,
+                strategy.max_amount,
+                strategy.fee
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    output.emit(human, json!(strategies));
+    Ok(())
+}
+
+fn quote(context: &AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let strategy = find_strategy(context, matches)?;
+    let amount = strategy_amount_arg(strategy, matches)?;
+
+    let prices = context.api.quote.get_price(&strategy.order_pair(), &amount.to_string(), false)?;
+
+    // every solver quoting the pair, best first and quotes that could not be read last
+    let mut quotes: Vec<(&String, &String, Result<u64, String>)> = prices
+        .iter()
+        .map(|(strategy_id, price)| (strategy_id, price, parse_quote(price)))
+        .collect();
+    quotes.sort_by_key(|(_, _, out_amount)| std::cmp::Reverse(out_amount.as_ref().ok().copied()));
+
+    let mut human = Vec::new();
+    let mut values = Vec::new();
+    for (strategy_id, price, out_amount) in quotes {
+        let quote_strategy = context.api.quote.strategies_map.get(strategy_id);
+        let warning = match (quote_strategy, &out_amount) {
+            (_, Err(e)) => Some(e.clone()),
+            (Some(quote_strategy), Ok(out_amount)) => quote_strategy.price_warning(amount, *out_amount),
+            (None, Ok(_)) => None,
+        };
+        human.push(format!(
+            "{}  {} {} -> {}{}{}",
+            strategy_id,
+            strategy.order_pair(),
+            amount,
+            price,
+            quote_strategy
+                .map(|quote_strategy| format!("  fee {} bips  {} confs  maker {}",
+                    quote_strategy.fee,
+                    quote_strategy.min_source_confirmations,
+                    quote_strategy.makers.join(", ")))
+                .unwrap_or_default(),
+            warning.as_ref().map(|warning| format!("  warning: {}", warning)).unwrap_or_default()
+        ));
+        values.push(json!({
+            "strategy": strategy_id,
+            "quote": price,
+            "fee": quote_strategy.map(|quote_strategy| quote_strategy.fee),
+            "makers": quote_strategy.map(|quote_strategy| &quote_strategy.makers),
+            "min_confirmations": quote_strategy.map(|quote_strategy| quote_strategy.min_source_confirmations),
+            "warning": warning,
+        }));
+    }
+
+    output.emit(
+        human.join("\n"),
+        json!({ "amount": amount.to_string(), "quotes": values }),
+    );
+    Ok(())
+}
+
+fn swap(mut context: AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let strategy = find_strategy(&context, matches)?.clone();
+    let amount = strategy_amount_arg(&strategy, matches)?;
+
+    let prices = context.api.quote.get_price(&strategy.order_pair(), &amount.to_string(), false)?;
+    let price = prices.get(&strategy.id).ok_or_else(|| {
+        CliError::new(exit_code::NOT_FOUND, format!("strategy {} did not quote, see `quote` for the others", strategy.id))
+    })?;
+    let out_amount = parse_quote(price).map_err(|e| CliError::new(exit_code::FAILURE, e))?;
+    output.progress(&format!("Quoted {} -> {}", amount, out_amount));
+    if let Some(warning) = strategy.price_warning(amount, out_amount) {
+        output.progress(&format!("warning: {}", warning));
+    }
+
+    let (order, secret) = build_order(&context, &strategy, amount, out_amount)?;
+    let attested_order = context.api.quote.get_attested_quote(order)?;
+
+    // journal the secret before anything is sent to the orderbook
+    let mut entry = JournalEntry::new(attested_order.clone(), secret);
+    context.journal.record(entry.clone())?;
+    context.order.current_strategy = Some(strategy.id.clone());
+    context.order.current_order = Some(attested_order);
+    context.order.secret = secret;
+
+    let mut progress = OrderProgress::NotStarted;
+    let mut order_id = String::new();
+    let mut step_started = Instant::now();
+
+    while progress != OrderProgress::Redeemed {
+        let result = match progress {
+            OrderProgress::NotStarted => create_order(&context),
+            OrderProgress::OrderCreated => initiate_source(&context, &order_id),
+            OrderProgress::Initialized => poll_destination(&context, &order_id),
+            OrderProgress::DestinationInitialized => redeem_destination(&context, &order_id),
+            _ => unreachable!("swap loop only runs until redeemed"),
+        };
+
+        let outcome: StepOutcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                entry.update(&order_id, &OrderProgress::Failed(format!("{:#}", e)), None);
+                context.journal.record(entry)?;
+                return Err(e.into());
+            }
+        };
+
+        if let Some(id) = &outcome.order_id {
+            order_id = id.clone();
+        }
+        entry.update(&order_id, &outcome.progress, outcome.matched_order.as_ref());
+        context.journal.record(entry.clone())?;
+        output.progress(&outcome.status);
+
+        if outcome.progress != progress {
+            progress = outcome.progress;
+            step_started = Instant::now();
+            continue;
+        }
+
+        if let Some(timeout) = exceeded_step_timeout(&progress, step_started.elapsed()) {
+            return Err(CliError::new(
+                exit_code::TIMEOUT,
+                format!(
+                    "order {} timed out after {}s at {:?}, the secret is kept in {}",
+                    order_id,
+                    timeout.as_secs(),
+                    progress,
+                    context.journal.path().display()
+                ),
+            ));
+        }
+        sleep(POLL_INTERVAL);
+    }
+
+    output.emit(
+        format!("Swap {} redeemed", order_id),
+        json!({ "order_id": order_id, "progress": progress }),
+    );
+    Ok(())
+}
+
+fn status(context: &AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+    let matched_order: MatchedOrder = context.api.orderbook.get_matched_order(order_id)?;
+
+    let human = format!(
+        "order {}\nsource      {}\ndestination {}",
+        order_id,
+        describe_swap(&matched_order.source_swap),
+        describe_swap(&matched_order.destination_swap)
+    );
+
+    output.emit(
+        human,
+        serde_json::to_value(&matched_order).map_err(anyhow::Error::from)?,
+    );
+    Ok(())
+}
+
+fn redeem(mut context: AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+
+    let mut entry = secret_entry(&context, order_id)?;
+
+    context.order.current_strategy = Some(entry.order.additional_data.strategy_id.clone());
+    context.order.current_order = Some(entry.order.clone());
+    context.order.secret = entry.secret_bytes()?;
+
+    let outcome = redeem_destination(&context, order_id)?;
+    entry.update(order_id, &outcome.progress, outcome.matched_order.as_ref());
+    context.journal.record(entry)?;
+
+    output.emit(
+        outcome.status.clone(),
+        json!({ "order_id": order_id, "progress": outcome.progress, "status": outcome.status }),
+    );
+    Ok(())
+}
+
+fn refund(context: &AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+
+    let counterparty_signature = matches.get_one::<String>("counterparty_sig").map(String::as_str);
+
+    let outcome = refund_source(context, order_id, counterparty_signature)?;
+    if let Some(mut entry) = context.journal.find_by_order_id(order_id)? {
+        entry.update(order_id, &outcome.progress, outcome.matched_order.as_ref());
+        context.journal.record(entry)?;
+    }
+
+    output.emit(
+        outcome.status.clone(),
+        json!({ "order_id": order_id, "progress": outcome.progress, "status": outcome.status }),
+    );
+    Ok(())
+}
+
+fn export_secret(context: &AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+    let entry = secret_entry(context, order_id)?;
+
+    output.emit(
+        entry.secret.clone(),
+        json!({ "order_id": order_id, "secret": entry.secret, "secret_hash": entry.secret_hash() }),
+    );
+    Ok(())
+}
+
+fn import_secret(context: &AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+    let secret = matches
+        .get_one::<String>("secret")
+        .expect("secret is required");
+    let secret = parse_secret(secret).map_err(|e| CliError::new(exit_code::USAGE, format!("{:#}", e)))?;
+
+    let matched_order = find_order(context, order_id)?;
+    if !secret_matches(&matched_order.create_order, &secret) {
+        return Err(CliError::new(
+            exit_code::USAGE,
+            format!("the secret does not match the secret hash of order {}", order_id),
+        ));
+    }
+
+    let mut entry = match context.journal.find_by_order_id(order_id)? {
+        Some(mut entry) => {
+            entry.secret = hex::encode(secret);
+            entry
+        }
+        None => JournalEntry::new(matched_order.create_order.clone(), secret),
+    };
+    entry.update(order_id, &infer_progress(&matched_order), Some(&matched_order));
+    let progress = entry.progress.clone();
+    context.journal.record(entry)?;
+
+    output.emit(
+        format!("Secret for order {} saved to {}", order_id, context.journal.path().display()),
+        json!({ "order_id": order_id, "progress": progress }),
+    );
+    Ok(())
+}
+
+fn export_psbt_cmd(mut context: AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+    let action = action_arg(matches);
+
+    // a redeem pays the order's bitcoin recipient when it has one
+    if let Some(entry) = context.journal.find_by_order_id(order_id)? {
+        context.order.current_order = Some(entry.order);
+    }
+
+    let psbt = export_psbt(&context, order_id, action)?;
+    output.emit(psbt.clone(), json!({ "order_id": order_id, "psbt": psbt }));
+    Ok(())
+}
+
+fn import_psbt_cmd(mut context: AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+    let action = action_arg(matches);
+    let psbt = matches.get_one::<String>("psbt").expect("psbt is required");
+
+    // the secret only goes into the redeem witness here, it never leaves for the external signer
+    let entry = match action {
+        PsbtAction::Redeem => {
+            let entry = secret_entry(&context, order_id)?;
+            context.order.current_order = Some(entry.order.clone());
+            context.order.secret = entry.secret_bytes()?;
+            Some(entry)
+        }
+        _ => context.journal.find_by_order_id(order_id)?,
+    };
+
+    let outcome = import_psbt(&context, order_id, action, psbt)?;
+    if let Some(mut entry) = entry {
+        entry.update(order_id, &outcome.progress, outcome.matched_order.as_ref());
+        context.journal.record(entry)?;
+    }
+
+    output.emit(
+        outcome.status.clone(),
+        json!({ "order_id": order_id, "progress": outcome.progress, "status": outcome.status }),
+    );
+    Ok(())
+}
+
+fn unlock_psbt_cmd(context: &AppContext, matches: &ArgMatches, output: &Output) -> Result<(), CliError> {
+    let order_id = order_id_arg(matches);
+    let secret_hash = match context.journal.find_by_order_id(order_id)? {
+        Some(entry) => entry.secret_hash().to_string(),
+        None => find_order(context, order_id)?.source_swap.secret_hash,
+    };
+
+    context.journal.release_utxos(&secret_hash)?;
+    output.emit(
+        format!("Released the UTXOs locked for order {}", order_id),
+        json!({ "order_id": order_id, "unlocked": true }),
+    );
+    Ok(())
+}
+
+/// Journal entry holding the secret of an order. Orders missing from the journal are looked up
+/// so their secret can be derived, which only works when it was created with deterministic secrets.
+fn secret_entry(context: &AppContext, order_id: &str) -> Result<JournalEntry, CliError> {
+    if let Some(entry) = context.journal.find_by_order_id(order_id)? {
+        return Ok(entry);
+    }
+
+    let matched_order = find_order(context, order_id)?;
+    let secret = recover_secret(context.wallet.signer.as_ref(), &matched_order.create_order).ok_or_else(|| {
+        CliError::new(
+            exit_code::NOT_FOUND,
+            format!("no secret for order {} in {}", order_id, context.journal.path().display()),
+        )
+    })?;
+
+    let mut entry = JournalEntry::new(matched_order.create_order.clone(), secret);
+    entry.update(order_id, &infer_progress(&matched_order), Some(&matched_order));
+    context.journal.record(entry.clone())?;
+    Ok(entry)
+}
+
+fn find_order(context: &AppContext, order_id: &str) -> Result<MatchedOrder, CliError> {
+    Ok(context.api.orderbook.get_matched_order(order_id)?)
+}
+
+fn find_strategy<'a>(context: &'a AppContext, matches: &ArgMatches) -> Result<&'a Strategy, CliError> {
+    let strategy_id = matches
+        .get_one::<String>("strategy")
+        .expect("strategy is required");
+
+    context
+        .api
+        .quote
+        .strategies_map
+        .get(strategy_id)
+        .ok_or_else(|| CliError::new(exit_code::NOT_FOUND, format!("unknown strategy {}", strategy_id)))
+}
+
+fn amount_arg(matches: &ArgMatches) -> Result<u64, CliError> {
+    let amount = matches.get_one::<String>("amount").expect("amount is required");
+    amount
+        .parse::<u64>()
+        .map_err(|e| CliError::new(exit_code::USAGE, format!("invalid amount {}: {}", amount, e)))
+}
+
+/// The amount argument, rejected with a usage error when outside the strategy's limits.
+fn strategy_amount_arg(strategy: &Strategy, matches: &ArgMatches) -> Result<u64, CliError> {
+    let amount = amount_arg(matches)?;
+    strategy
+        .check_amount(amount)
+        .map_err(|e| CliError::new(exit_code::USAGE, e))?;
+    Ok(amount)
+}
+
+fn action_arg(matches: &ArgMatches) -> PsbtAction {
+    match matches.get_one::<String>("action").map(String::as_str) {
+        Some("redeem") => PsbtAction::Redeem,
+        Some("refund") => PsbtAction::Refund,
+        _ => PsbtAction::Initiate,
+    }
+}
+
+fn order_id_arg(matches: &ArgMatches) -> &str {
+    matches
+        .get_one::<String>("order_id")
+        .expect("order id is required")
+}
+
+fn describe_swap(swap: &SingleSwap) -> String {
+    let tx = |hash: &Option<String>| match hash.as_deref() {
+        Some(hash) if !hash.is_empty() => hash.to_string(),
+        _ => "-".to_string(),
+    };
+
+    format!(
+        "{} {} amount {} | initiate {} ({}/{} confs) | redeem {} | refund {}",
+        swap.chain,
+        swap.asset,
+        swap.amount,
+        tx(&swap.initiate_tx_hash),
+        swap.current_confirmations,
+        swap.required_confirmations,
+        tx(&swap.redeem_tx_hash),
+        tx(&swap.refund_tx_hash)
+    )
+}
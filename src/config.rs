@@ -5,6 +5,9 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use crate::service::blockchain::bitcoin::{coin_selection::SelectionStrategy, fee::FeePriority};
+use crate::service::garden::siwe::SiweConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub networks: HashMap<String, NetworkConfig>,
@@ -14,6 +17,57 @@ pub struct Config {
 pub struct NetworkConfig {
     pub api: ApiConfig,
     pub providers: HashMap<String, String>,
+    #[serde(default)]
+    pub quote: QuoteConfig,
+    /// Derive order secrets from the wallet key and order nonce instead of fresh randomness,
+    /// so they can be regenerated if the journal is lost.
+    #[serde(default)]
+    pub deterministic_secrets: bool,
+    /// Confirmation target of the Bitcoin transactions, until changed in the order dashboard.
+    #[serde(default)]
+    pub fee_priority: FeePriority,
+    #[serde(default)]
+    pub coin_selection: CoinSelectionConfig,
+}
+
+/// How the swap screen keeps its quote fresh, in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteConfig {
+    pub refresh_interval: u64,
+    /// Quotes older than this are not used to create orders.
+    pub max_age: u64,
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        QuoteConfig {
+            refresh_interval: 15,
+            max_age: 60,
+        }
+    }
+}
+
+/// How the UTXOs funding a Bitcoin HTLC are picked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoinSelectionConfig {
+    #[serde(default)]
+    pub strategy: SelectionStrategy,
+    /// Leave outputs of unconfirmed transactions alone, they may still be replaced or dropped.
+    #[serde(default = "default_confirmed_only")]
+    pub confirmed_only: bool,
+}
+
+impl Default for CoinSelectionConfig {
+    fn default() -> Self {
+        CoinSelectionConfig {
+            strategy: SelectionStrategy::default(),
+            confirmed_only: default_confirmed_only(),
+        }
+    }
+}
+
+fn default_confirmed_only() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +76,7 @@ pub struct ApiConfig {
     pub authenticator_url: String,
     pub evm_relayer_url: String,
     pub orderbook_url: String,
+    pub siwe: SiweConfig,
 }
 
 impl Config {
@@ -50,3 +105,51 @@ impl Config {
             .with_context(|| format!("Network '{}' not found in configuration", network_name))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network_json(api_extra: &str) -> String {
+        format!(
+            r#"{{
+                "networks": {{
+                    "testnet": {{
+                        "api": {{
+                            "quote_server_url": "http://quote",
+                            "authenticator_url": "http://auth",
+                            "evm_relayer_url": "http://relayer",
+                            "orderbook_url": "http://orders"{}
+                        }},
+                        "providers": {{}}
+                    }}
+                }}
+            }}"#,
+            api_extra
+        )
+    }
+
+    #[test]
+    fn parses_network_with_siwe() {
+        let json = network_json(
+            r#", "siwe": { "domain": "testnet.garden.finance", "uri": "https://testnet.garden.finance", "chain_id": 11155111, "statement": null }"#,
+        );
+        let config = Config::from_json(&json).unwrap();
+        let siwe = &config.get_network("testnet").unwrap().api.siwe;
+        assert_eq!(siwe.domain, "testnet.garden.finance");
+        assert_eq!(siwe.chain_id, 11155111);
+        assert_eq!(siwe.message_ttl, 300);
+    }
+
+    #[test]
+    fn rejects_network_without_siwe() {
+        let err = Config::from_json(&network_json("")).unwrap_err();
+        assert!(format!("{:#}", err).contains("missing field `siwe`"), "{:#}", err);
+    }
+
+    #[test]
+    fn parses_shipped_config() {
+        let config = Config::from_json(include_str!("../config.json")).unwrap();
+        assert_eq!(config.get_network("mainnet").unwrap().api.siwe.chain_id, 1);
+    }
+}
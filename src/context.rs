@@ -1,11 +1,11 @@
-use std::{collections::HashMap, env, str::FromStr, sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use alloy::signers::{k256::ecdsa::SigningKey, local::{LocalSigner, PrivateKeySigner}};
-use bitcoin::{key::Secp256k1, Address, CompressedPublicKey, PrivateKey, PublicKey};
+use anyhow::Context;
+use bitcoin::CompressedPublicKey;
 use reqwest::cookie::Jar;
 
 
-use crate::{config::{ApiConfig, NetworkConfig}, service::garden::{orderbook::Orderbook, quote::Quote, types::Order}};
+use crate::{config::{ApiConfig, CoinSelectionConfig, NetworkConfig, QuoteConfig}, journal::Journal, keystore::{Derivation, Wallet}, service::{blockchain::bitcoin::{fee::FeePriority, wallet::WalletScript}, garden::{error::GardenError, orderbook::Orderbook, quote::Quote, types::Order}, signer::SharedSigner}};
 
 #[derive(Clone)]
 pub struct AppContext {
@@ -15,61 +15,78 @@ pub struct AppContext {
     pub wallet: WalletContext,
     pub api: APIContext,
     pub order: OrderContext,
+    pub journal: Journal,
+    pub quote_config: QuoteConfig,
+    pub deterministic_secrets: bool,
+    pub fee_priority: FeePriority,
+    pub coin_selection: CoinSelectionConfig,
 }
 
 impl AppContext {
-    pub fn new(selected_network: &str, config: &NetworkConfig) -> Self {
+    pub fn new(selected_network: &str, config: &NetworkConfig, wallet: Wallet) -> anyhow::Result<Self> {
         
-        let wallet = WalletContext::new(selected_network, config.providers.clone());
-        let api = APIContext::new(config.api.clone(), &wallet.signer);
+        let wallet = WalletContext::new(selected_network, wallet, config.providers.clone());
+        let api = APIContext::new(config.api.clone(), &wallet.signer)?;
         let order = OrderContext::default();
+        let journal = Journal::open(selected_network).context("failed to open swap journal")?;
         
-        Self {
+        Ok(Self {
             selected_network: selected_network.to_string(),
             exit_message: None,
             wallet,
             api,
-            order
-        }
+            order,
+            journal,
+            quote_config: config.quote.clone(),
+            deterministic_secrets: config.deterministic_secrets,
+            fee_priority: config.fee_priority,
+            coin_selection: config.coin_selection.clone(),
+        })
+    }
+}
+
+pub fn btc_network(network: &str) -> bitcoin::Network {
+    match network {
+        "mainnet" => bitcoin::Network::Bitcoin,
+        "testnet" => bitcoin::Network::Testnet4,
+        _ => bitcoin::Network::Regtest  
     }
 }
 
 #[derive(Clone)]
 pub struct WalletContext {
-    pub signer: LocalSigner<SigningKey>,
+    pub signer: SharedSigner,
     pub btc_network: bitcoin::Network,
-    pub btc_private_key: bitcoin::PrivateKey,
     pub btc_xpubkey: String,
     pub btc_address: String,
-    pub provider_urls: HashMap<String, String>
+    /// Script type of the wallet's own outputs, taproot for BIP86 keys.
+    pub btc_script: WalletScript,
+    pub provider_urls: HashMap<String, String>,
+    /// Paths the keys were derived at, when they came from a seed phrase.
+    pub derivation: Option<Derivation>,
 }
 
 impl WalletContext {
-    fn new(network: &str, provider_urls: HashMap<String, String>) -> WalletContext {
-        let eth_priv_key = env::var("PRIV_KEY").expect("please provide a valid PRIV_KEY in env");
-        let btc_priv_key = env::var("BTC_PRIV_KEY").unwrap_or(eth_priv_key.clone());
-        
-        let signer = PrivateKeySigner::from_str(&eth_priv_key).expect("ERR CREATING ETH SIGNER");
-        let btc_network = match network {
-            "mainnet" => bitcoin::Network::Bitcoin,
-            "testnet" => bitcoin::Network::Testnet4,
-            _ => bitcoin::Network::Regtest  
-        };
+    fn new(network: &str, wallet: Wallet, provider_urls: HashMap<String, String>) -> WalletContext {
+        let signer = wallet.signer;
+        let btc_network = btc_network(network);
         
-        let priv_key_bytes = hex::decode(&btc_priv_key).unwrap();
-        let btc_private_key = PrivateKey::from_slice(&priv_key_bytes, btc_network).unwrap();
-        let secp = Secp256k1::new();
-        let pubkey = PublicKey::from_private_key(&secp, &btc_private_key);
-        let btc_pubkey = CompressedPublicKey::try_from(pubkey).unwrap();
-        let btc_address = Address::p2wpkh(&btc_pubkey, btc_network).to_string();
+        let btc_pubkey = CompressedPublicKey::try_from(signer.btc_public_key())
+            .expect("signers only hand out compressed BTC keys");
+        let btc_script = wallet.derivation.as_ref().map(Derivation::btc_script).unwrap_or_default();
+        let btc_address = btc_script
+            .address(&signer.btc_public_key(), btc_network)
+            .expect("signers only hand out compressed BTC keys")
+            .to_string();
         
         Self {
             signer,
             btc_network,
-            btc_private_key,
             btc_address,
+            btc_script,
             btc_xpubkey: btc_pubkey.to_string()[2..].to_string(),
-            provider_urls
+            provider_urls,
+            derivation: wallet.derivation,
         }
     }
 }
@@ -81,37 +98,26 @@ pub struct APIContext {
 }
 
 impl APIContext {
-    fn new(api_urls: ApiConfig, signer: &PrivateKeySigner) -> Self {
+    fn new(api_urls: ApiConfig, signer: &SharedSigner) -> Result<Self, GardenError> {
         let cookie_store = Arc::new(Jar::default());
         let client = reqwest::blocking::ClientBuilder::new()
             .timeout(Duration::from_secs(5))
             .cookie_provider(cookie_store.clone())
-            .build()
-            .unwrap();
+            .build()?;
         
-        let quote = Quote::new(client.clone(), api_urls.quote_server_url).unwrap();
-        let orderbook = Orderbook::new(client, &api_urls.evm_relayer_url, &api_urls.authenticator_url, &api_urls.orderbook_url, signer);
+        let quote = Quote::new(client.clone(), api_urls.quote_server_url)?;
+        let orderbook = Orderbook::new(client, &api_urls.evm_relayer_url, &api_urls.authenticator_url, &api_urls.orderbook_url, signer.clone(), api_urls.siwe.clone())?;
         
-        Self {
+        Ok(Self {
             quote,
             orderbook
-        }
+        })
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct OrderContext {
     pub current_strategy: Option<String>,
     pub current_order: Option<Order>,
     pub secret: [u8; 32],
 }
-
-impl Default for OrderContext {
-    fn default() -> Self {
-        Self {
-            current_strategy: None,
-            current_order: None,
-            secret: [0; 32],
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,535 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::service::garden::types::{MatchedOrder, Order};
+use crate::swap::OrderProgress;
+
+/// A single swap as persisted on disk. Entries are keyed by the order's secret hash,
+/// which exists from the moment the order is attested, before an order id is assigned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub order_id: Option<String>,
+    pub order: Order,
+    pub secret: String,
+    pub matched_order: Option<MatchedOrder>,
+    pub progress: OrderProgress,
+    pub last_error: Option<String>,
+    pub updated_at: String,
+}
+
+impl JournalEntry {
+    pub fn new(order: Order, secret: [u8; 32]) -> Self {
+        Self {
+            order_id: None,
+            order,
+            secret: hex::encode(secret),
+            matched_order: None,
+            progress: OrderProgress::NotStarted,
+            last_error: None,
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn secret_hash(&self) -> &str {
+        &self.order.secret_hash
+    }
+
+    pub fn secret_bytes(&self) -> Result<[u8; 32]> {
+        let bytes = hex::decode(&self.secret).context("journal secret is not valid hex")?;
+        let secret: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("journal secret must be 32 bytes"))?;
+        Ok(secret)
+    }
+
+    /// Applies a step's progress. `Failed` keeps the last successful progress and only
+    /// records the error, so a resume retries the step.
+    pub fn update(&mut self, order_id: &str, progress: &OrderProgress, matched_order: Option<&MatchedOrder>) {
+        match progress {
+            OrderProgress::Failed(reason) => self.last_error = Some(reason.clone()),
+            progress => {
+                if !matches!(progress, OrderProgress::NotStarted) {
+                    self.order_id = Some(order_id.to_string());
+                }
+                self.progress = progress.clone();
+                self.last_error = None;
+            }
+        }
+
+        if let Some(matched_order) = matched_order {
+            self.matched_order = Some(matched_order.clone());
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.progress, OrderProgress::Redeemed | OrderProgress::Refunded)
+    }
+
+    /// Folds in `existing`, the entry on disk. Another process may have recorded a later step
+    /// since this entry was read, so the furthest progress and any tx hash already seen are kept.
+    fn merge(&mut self, existing: &JournalEntry) {
+        if progress_rank(&existing.progress) > progress_rank(&self.progress) {
+            self.progress = existing.progress.clone();
+            self.last_error = existing.last_error.clone();
+        }
+        if self.order_id.is_none() {
+            self.order_id = existing.order_id.clone();
+        }
+
+        match (&mut self.matched_order, &existing.matched_order) {
+            (None, Some(existing)) => self.matched_order = Some(existing.clone()),
+            (Some(matched_order), Some(existing)) => {
+                for (swap, existing) in [
+                    (&mut matched_order.source_swap, &existing.source_swap),
+                    (&mut matched_order.destination_swap, &existing.destination_swap),
+                ] {
+                    keep_tx(&mut swap.initiate_tx_hash, &existing.initiate_tx_hash);
+                    keep_tx(&mut swap.redeem_tx_hash, &existing.redeem_tx_hash);
+                    keep_tx(&mut swap.refund_tx_hash, &existing.refund_tx_hash);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the order may still broadcast a funding PSBT exported before its HTLC was funded.
+    /// A failed step keeps the progress it failed at, so errors don't release the outputs.
+    fn holds_utxo_lock(&self) -> bool {
+        matches!(self.progress, OrderProgress::NotStarted | OrderProgress::OrderCreated)
+    }
+}
+
+/// Order of the steps, `Failed` is never recorded as progress.
+fn progress_rank(progress: &OrderProgress) -> u8 {
+    match progress {
+        OrderProgress::NotStarted | OrderProgress::Failed(_) => 0,
+        OrderProgress::OrderCreated => 1,
+        OrderProgress::Initialized => 2,
+        OrderProgress::DestinationInitialized => 3,
+        OrderProgress::Redeemed | OrderProgress::Refunded => 4,
+    }
+}
+
+fn keep_tx(hash: &mut Option<String>, existing: &Option<String>) {
+    if hash.as_deref().is_none_or(str::is_empty) {
+        hash.clone_from(existing);
+    }
+}
+
+/// Per-network on-disk record of every swap started from this machine.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+    /// Wallet outputs, as `txid:vout`, spent by exported funding PSBTs that have not been
+    /// broadcast yet, keyed by secret hash. Kept apart from the entries so orders missing from
+    /// the journal can lock outputs too.
+    locks_path: PathBuf,
+}
+
+impl Journal {
+    pub fn open(network: &str) -> Result<Self> {
+        let dir = journal_dir();
+        create_private_dir(&dir)
+            .with_context(|| format!("Failed to create journal directory at {}", dir.display()))?;
+
+        Ok(Self {
+            path: dir.join(format!("{}.json", network)),
+            locks_path: dir.join(format!("{}.locks.json", network)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let contents = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read journal at {}", self.path.display()))?;
+
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse journal at {}", self.path.display()))
+    }
+
+    pub fn unfinished(&self) -> Result<Vec<JournalEntry>> {
+        let mut entries: Vec<JournalEntry> = self
+            .entries()?
+            .into_iter()
+            .filter(|entry| !entry.is_finished())
+            .collect();
+        entries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(entries)
+    }
+
+    /// Picks outputs for the order with `secret_hash` and locks them, replacing what it locked
+    /// before. `select` gets the outputs held by other orders that may still fund their HTLC
+    /// and returns its result with the outputs to lock. The journal lock is held from reading
+    /// the locks to writing them, so concurrent selections never pick the same outputs.
+    pub fn lock_utxos<T>(
+        &self,
+        secret_hash: &str,
+        select: impl FnOnce(&[String]) -> Result<(T, Vec<String>)>,
+    ) -> Result<T> {
+        let _guard = self.lock_exclusive()?;
+        let entries = self.entries()?;
+        let mut locks = self.locks()?;
+
+        let locked: Vec<String> = locks
+            .iter()
+            .filter(|(hash, _)| hash.as_str() != secret_hash)
+            .filter(|(hash, _)| {
+                entries
+                    .iter()
+                    .find(|entry| entry.secret_hash() == hash.as_str())
+                    .is_none_or(JournalEntry::holds_utxo_lock)
+            })
+            .flat_map(|(_, outpoints)| outpoints.iter().cloned())
+            .collect();
+
+        let (value, outpoints) = select(&locked)?;
+        locks.insert(secret_hash.to_string(), outpoints);
+        write_private(&self.locks_path, &locks)?;
+        Ok(value)
+    }
+
+    pub fn release_utxos(&self, secret_hash: &str) -> Result<()> {
+        let _guard = self.lock_exclusive()?;
+        self.release_utxos_locked(secret_hash)
+    }
+
+    fn release_utxos_locked(&self, secret_hash: &str) -> Result<()> {
+        let mut locks = self.locks()?;
+        if locks.remove(secret_hash).is_some() {
+            write_private(&self.locks_path, &locks)?;
+        }
+        Ok(())
+    }
+
+    fn locks(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        if !self.locks_path.exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        let contents = fs::read_to_string(&self.locks_path)
+            .with_context(|| format!("Failed to read UTXO locks at {}", self.locks_path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse UTXO locks at {}", self.locks_path.display()))
+    }
+
+    pub fn get(&self, secret_hash: &str) -> Result<Option<JournalEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .find(|entry| entry.secret_hash() == secret_hash))
+    }
+
+    pub fn find_by_order_id(&self, order_id: &str) -> Result<Option<JournalEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .find(|entry| entry.order_id.as_deref() == Some(order_id)))
+    }
+
+    /// Inserts the entry, or merges it into the one with the same secret hash, and flushes the
+    /// journal to disk. Outputs locked by the order are released once it is funded or refunded.
+    pub fn record(&self, mut entry: JournalEntry) -> Result<()> {
+        // the TUI and CLI may record at the same time, and each rewrites the whole file
+        let _guard = self.lock_exclusive()?;
+
+        let mut entries = self.entries()?;
+        let existing = entries
+            .iter_mut()
+            .find(|existing| existing.secret_hash() == entry.secret_hash());
+        if let Some(existing) = &existing {
+            entry.merge(existing);
+        }
+
+        entry.updated_at = Utc::now().to_rfc3339();
+        if !entry.holds_utxo_lock() {
+            self.release_utxos_locked(entry.secret_hash())?;
+        }
+
+        match existing {
+            Some(existing) => *existing = entry,
+            None => entries.push(entry),
+        }
+
+        write_private(&self.path, &entries)
+    }
+
+    /// Advisory lock serializing writers of this network's journal across processes, held
+    /// until the returned file is dropped. Readers don't take it, as every write is a rename.
+    fn lock_exclusive(&self) -> Result<File> {
+        let lock_path = self.path.with_extension("json.lock");
+        let file = create_private_file(&lock_path)
+            .with_context(|| format!("Failed to open journal lock at {}", lock_path.display()))?;
+        file.lock_exclusive()
+            .with_context(|| format!("Failed to lock journal at {}", lock_path.display()))?;
+        Ok(file)
+    }
+}
+
+// write to a temporary file and rename so a crash mid-write never truncates the journal
+fn write_private<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    let contents = serde_json::to_string_pretty(value).context("Failed to serialize journal")?;
+
+    let mut file = create_private_file(&tmp_path)
+        .with_context(|| format!("Failed to create journal file at {}", tmp_path.display()))?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to write journal at {}", path.display()))?;
+    Ok(())
+}
+
+fn journal_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".garden-tui").join("journal")
+}
+
+// the journal holds swap secrets, so keep it readable by the owner only
+#[cfg(unix)]
+fn create_private_file(path: &Path) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private_file(path: &Path) -> std::io::Result<File> {
+    File::create(path)
+}
+
+// journals written before the directory was private are tightened as well
+#[cfg(unix)]
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use bigdecimal::BigDecimal;
+
+    use super::*;
+    use crate::service::garden::types::AdditionalData;
+
+    // every test points HOME at its own directory, so they must not run at the same time
+    static HOME_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_home(name: &str, test: impl FnOnce(Journal)) {
+        let _guard = HOME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let home = std::env::temp_dir().join(format!("garden-tui-journal-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+
+        let previous = std::env::var("HOME").ok();
+        std::env::set_var("HOME", &home);
+        let journal = Journal::open("localnet");
+        match previous {
+            Some(previous) => std::env::set_var("HOME", previous),
+            None => std::env::remove_var("HOME"),
+        }
+
+        test(journal.unwrap());
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    fn entry(secret_hash: &str) -> JournalEntry {
+        let order = Order {
+            create_id: None,
+            source_chain: "bitcoin_regtest".to_string(),
+            destination_chain: "ethereum_localnet".to_string(),
+            source_asset: "primary".to_string(),
+            destination_asset: "0x00".to_string(),
+            initiator_source_address: "02aa".to_string(),
+            initiator_destination_address: "0x00".to_string(),
+            source_amount: BigDecimal::from(50_000),
+            destination_amount: BigDecimal::from(49_000),
+            fee: BigDecimal::from(30),
+            nonce: BigDecimal::from(1),
+            min_destination_confirmations: 1,
+            timelock: 288,
+            secret_hash: secret_hash.to_string(),
+            additional_data: AdditionalData {
+                strategy_id: "btc-eth".to_string(),
+                bitcoin_optional_recipient: None,
+                input_token_price: None,
+                output_token_price: None,
+                sig: None,
+                deadline: None,
+            },
+        };
+        JournalEntry::new(order, [7; 32])
+    }
+
+    #[test]
+    fn record_inserts_then_replaces_by_secret_hash() {
+        with_home("record", |journal| {
+            journal.record(entry("aa")).unwrap();
+            journal.record(entry("bb")).unwrap();
+
+            let mut updated = entry("aa");
+            updated.update("order-1", &OrderProgress::OrderCreated, None);
+            journal.record(updated).unwrap();
+
+            let entries = journal.entries().unwrap();
+            assert_eq!(entries.len(), 2);
+            let recorded = journal.get("aa").unwrap().unwrap();
+            assert_eq!(recorded.progress, OrderProgress::OrderCreated);
+            assert_eq!(recorded.order_id.as_deref(), Some("order-1"));
+            assert_eq!(recorded.secret_bytes().unwrap(), [7; 32]);
+            assert_eq!(journal.find_by_order_id("order-1").unwrap().unwrap().secret_hash(), "aa");
+        });
+    }
+
+    fn matched_order(source_initiate: Option<&str>) -> MatchedOrder {
+        let swap = |initiate_tx_hash: Option<&str>| {
+            serde_json::json!({
+                "created_at": "", "updated_at": "", "deleted_at": null, "swap_id": "swap", "chain": "bitcoin_regtest",
+                "asset": "primary", "initiator": "02aa", "redeemer": "02bb", "timelock": 288,
+                "filled_amount": "0", "amount": "50000", "secret_hash": "aa", "secret": null,
+                "initiate_tx_hash": initiate_tx_hash, "redeem_tx_hash": null, "refund_tx_hash": null,
+                "initiate_block_number": null, "redeem_block_number": null, "refund_block_number": null,
+                "required_confirmations": 1, "current_confirmations": 0,
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "created_at": "", "updated_at": "", "deleted_at": null,
+            "source_swap": swap(source_initiate),
+            "destination_swap": swap(None),
+            "create_order": entry("aa").order,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn record_keeps_the_furthest_progress_and_known_tx_hashes() {
+        with_home("merge", |journal| {
+            // read by two processes before either recorded anything
+            let mut stale = entry("aa");
+            let mut funded = entry("aa");
+
+            funded.update("order-1", &OrderProgress::Initialized, Some(&matched_order(Some("0xfund"))));
+            journal.record(funded).unwrap();
+
+            stale.update("order-1", &OrderProgress::OrderCreated, Some(&matched_order(None)));
+            journal.record(stale).unwrap();
+
+            let recorded = journal.get("aa").unwrap().unwrap();
+            assert_eq!(recorded.progress, OrderProgress::Initialized);
+            let matched_order = recorded.matched_order.unwrap();
+            assert_eq!(matched_order.source_swap.initiate_tx_hash.as_deref(), Some("0xfund"));
+
+            // a later step still moves the entry forward
+            let mut redeemed = entry("aa");
+            redeemed.update("order-1", &OrderProgress::Redeemed, None);
+            journal.record(redeemed).unwrap();
+            let recorded = journal.get("aa").unwrap().unwrap();
+            assert_eq!(recorded.progress, OrderProgress::Redeemed);
+            assert!(recorded.matched_order.is_some());
+        });
+    }
+
+    #[test]
+    fn update_keeps_the_progress_on_failure() {
+        let mut entry = entry("aa");
+        entry.update("order-1", &OrderProgress::Initialized, None);
+        entry.update("order-1", &OrderProgress::Failed("timed out".to_string()), None);
+        assert_eq!(entry.progress, OrderProgress::Initialized);
+        assert_eq!(entry.last_error.as_deref(), Some("timed out"));
+
+        entry.update("order-1", &OrderProgress::DestinationInitialized, None);
+        assert_eq!(entry.progress, OrderProgress::DestinationInitialized);
+        assert_eq!(entry.last_error, None);
+    }
+
+    #[test]
+    fn unfinished_lists_the_latest_first() {
+        with_home("unfinished", |journal| {
+            for (secret_hash, progress) in [
+                ("aa", OrderProgress::OrderCreated),
+                ("bb", OrderProgress::Redeemed),
+                ("cc", OrderProgress::Initialized),
+                ("dd", OrderProgress::Refunded),
+            ] {
+                let mut entry = entry(secret_hash);
+                entry.update("order", &progress, None);
+                journal.record(entry).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+
+            let unfinished: Vec<String> = journal
+                .unfinished()
+                .unwrap()
+                .iter()
+                .map(|entry| entry.secret_hash().to_string())
+                .collect();
+            assert_eq!(unfinished, ["cc", "aa"]);
+        });
+    }
+
+    #[test]
+    fn utxo_locks_survive_failed_steps() {
+        with_home("locks", |journal| {
+            journal.record(entry("aa")).unwrap();
+            journal
+                .lock_utxos("aa", |locked| {
+                    assert!(locked.is_empty());
+                    Ok(((), vec!["00:0".to_string()]))
+                })
+                .unwrap();
+            let locked_by_others = |journal: &Journal| journal.lock_utxos("bb", |locked| Ok((locked.to_vec(), vec![]))).unwrap();
+
+            let mut failed = entry("aa");
+            failed.update("order-1", &OrderProgress::Failed("network error".to_string()), None);
+            journal.record(failed).unwrap();
+            assert_eq!(locked_by_others(&journal), ["00:0"]);
+
+            let mut funded = entry("aa");
+            funded.update("order-1", &OrderProgress::Initialized, None);
+            journal.record(funded).unwrap();
+            assert!(locked_by_others(&journal).is_empty());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        with_home("mode", |journal| {
+            journal.record(entry("aa")).unwrap();
+            journal.lock_utxos("aa", |_| Ok(((), vec![]))).unwrap();
+
+            for path in [journal.path().to_path_buf(), journal.locks_path.clone()] {
+                let mode = fs::metadata(&path).unwrap().permissions().mode();
+                assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+            }
+            let mode = fs::metadata(journal.path().parent().unwrap()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        });
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::{anyhow, bail, Context, Result};
+use alloy::signers::local::{coins_bip39::{English, Mnemonic}, MnemonicBuilder, PrivateKeySigner};
+use bitcoin::bip32::{DerivationPath, KeySource as KeyOrigin, Xpriv};
+use bitcoin::secp256k1::{Secp256k1, SecretKey};
+use clap::ArgMatches;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::service::blockchain::bitcoin::wallet::WalletScript;
+use crate::service::signer::{local::LocalWalletSigner, SharedSigner};
+
+/// Where the wallet's private keys are read from.
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    /// Ethereum JSON v3 keystores, decrypted with passwords prompted on the terminal.
+    /// Without a BTC keystore the EVM key is used for Bitcoin as well.
+    Keystore { evm: PathBuf, btc: Option<PathBuf> },
+    /// A BIP39 seed phrase prompted on the terminal, both keys derived from it.
+    Mnemonic(Derivation),
+    /// Raw hex keys in `PRIV_KEY` and `BTC_PRIV_KEY`, or a seed phrase in `MNEMONIC`,
+    /// only used when explicitly allowed.
+    Env(Derivation),
+    /// A signer daemon listening on a Unix socket, the keys never enter this process.
+    Remote(PathBuf),
+}
+
+/// Which keys to derive from a seed phrase.
+#[derive(Debug, Clone)]
+pub struct Derivation {
+    /// Last component of both paths, `m/44'/60'/0'/0/i` for EVM.
+    pub index: u32,
+    /// BIP84 (native segwit) or BIP86 (taproot) purpose of the Bitcoin path.
+    pub btc_purpose: u32,
+    /// `0` on mainnet and `1` on the test networks, as registered in SLIP-44.
+    pub btc_coin_type: u32,
+}
+
+impl Derivation {
+    pub fn evm_path(&self) -> String {
+        format!("m/44'/60'/0'/0/{}", self.index)
+    }
+
+    pub fn btc_path(&self) -> String {
+        format!("m/{}'/{}'/0'/0/{}", self.btc_purpose, self.btc_coin_type, self.index)
+    }
+
+    /// BIP86 keys fund the HTLC from a taproot key path address, BIP84 keys from P2WPKH.
+    pub fn btc_script(&self) -> WalletScript {
+        match self.btc_purpose {
+            86 => WalletScript::P2tr,
+            _ => WalletScript::P2wpkh,
+        }
+    }
+}
+
+impl KeySource {
+    pub fn from_matches(matches: &ArgMatches, btc_network: bitcoin::Network) -> Result<Self> {
+        let derivation = Derivation {
+            index: *matches.get_one::<u32>("account_index").expect("account index has a default"),
+            btc_purpose: matches
+                .get_one::<String>("btc_purpose")
+                .expect("btc purpose has a default")
+                .parse()
+                .context("--btc-purpose must be 84 or 86")?,
+            btc_coin_type: match btc_network {
+                bitcoin::Network::Bitcoin => 0,
+                _ => 1,
+            },
+        };
+
+        if let Some(socket) = matches.get_one::<String>("signer_socket") {
+            return Ok(KeySource::Remote(PathBuf::from(socket)));
+        }
+        if let Some(evm) = matches.get_one::<String>("keystore") {
+            return Ok(KeySource::Keystore {
+                evm: PathBuf::from(evm),
+                btc: matches.get_one::<String>("btc_keystore").map(PathBuf::from),
+            });
+        }
+        if matches.get_flag("mnemonic") {
+            return Ok(KeySource::Mnemonic(derivation));
+        }
+        if matches.get_flag("allow_env_keys") {
+            return Ok(KeySource::Env(derivation));
+        }
+        bail!("no wallet keys: pass --keystore <FILE>, --mnemonic or --signer-socket <PATH>, or --allow-env-keys to read PRIV_KEY and BTC_PRIV_KEY")
+    }
+}
+
+/// Signer for the wallet and, for seed phrases, the paths its keys were derived at.
+#[derive(Clone)]
+pub struct Wallet {
+    pub signer: SharedSigner,
+    pub derivation: Option<Derivation>,
+}
+
+impl Wallet {
+    pub fn open(source: &KeySource) -> Result<Self> {
+        match source {
+            #[cfg(unix)]
+            KeySource::Remote(socket) => Ok(Wallet {
+                signer: Arc::new(crate::service::signer::remote::RemoteWalletSigner::connect(socket)?),
+                derivation: None,
+            }),
+            #[cfg(not(unix))]
+            KeySource::Remote(_) => bail!("remote signers are only supported on Unix"),
+            source => {
+                let keys = WalletKeys::load(source)?;
+                let derivation = keys.derivation.clone();
+                Ok(Wallet {
+                    signer: Arc::new(keys.into_signer()),
+                    derivation,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WalletKeys {
+    pub evm: PrivateKeySigner,
+    pub btc: SecretKey,
+    /// Set when the keys were derived from a seed phrase.
+    pub derivation: Option<Derivation>,
+    /// Master fingerprint and path of the BTC key, when derived from a seed phrase.
+    pub btc_origin: Option<KeyOrigin>,
+}
+
+impl WalletKeys {
+    /// Reads and validates both keys. Must run before the TUI takes over the terminal,
+    /// since keystore passwords and seed phrases are prompted for.
+    pub fn load(source: &KeySource) -> Result<Self> {
+        match source {
+            KeySource::Keystore { evm, btc } => {
+                let evm = decrypt_keystore(evm, "EVM")?;
+                let btc = match btc {
+                    Some(path) => decrypt_keystore(path, "BTC")?.to_bytes(),
+                    None => evm.to_bytes(),
+                };
+                Ok(WalletKeys {
+                    btc: secret_key(btc.as_slice()).context("BTC keystore does not hold a valid key")?,
+                    evm,
+                    derivation: None,
+                    btc_origin: None,
+                })
+            }
+            KeySource::Mnemonic(derivation) => {
+                let phrase = rpassword::prompt_password("Seed phrase: ").context("Failed to read the seed phrase")?;
+                let passphrase = rpassword::prompt_password("BIP39 passphrase (empty for none): ")
+                    .context("Failed to read the BIP39 passphrase")?;
+                from_mnemonic(&phrase, &passphrase, derivation)
+            }
+            KeySource::Env(derivation) => {
+                if let Ok(phrase) = env::var("MNEMONIC") {
+                    let passphrase = env::var("MNEMONIC_PASSPHRASE").unwrap_or_default();
+                    return from_mnemonic(&phrase, &passphrase, derivation);
+                }
+
+                let evm_key = env::var("PRIV_KEY").context("PRIV_KEY is not set")?;
+                let evm = PrivateKeySigner::from_str(&evm_key).context("PRIV_KEY is not a valid private key")?;
+                let btc = match env::var("BTC_PRIV_KEY") {
+                    Ok(btc_key) => {
+                        let bytes = hex::decode(btc_key.trim().trim_start_matches("0x"))
+                            .context("BTC_PRIV_KEY is not valid hex")?;
+                        secret_key(&bytes).context("BTC_PRIV_KEY is not a valid private key")?
+                    }
+                    Err(_) => secret_key(evm.to_bytes().as_slice())?,
+                };
+                Ok(WalletKeys { evm, btc, derivation: None, btc_origin: None })
+            }
+            KeySource::Remote(socket) => bail!("the keys of {} are held by the signer daemon", socket.display()),
+        }
+    }
+
+    pub fn into_signer(self) -> LocalWalletSigner {
+        LocalWalletSigner::new(self.evm, self.btc).with_btc_origin(self.btc_origin)
+    }
+}
+
+fn from_mnemonic(phrase: &str, passphrase: &str, derivation: &Derivation) -> Result<WalletKeys> {
+    let derive = |path: &str| -> Result<PrivateKeySigner> {
+        let mut builder = MnemonicBuilder::<English>::default()
+            .phrase(phrase.trim())
+            .derivation_path(path)
+            .with_context(|| format!("invalid derivation path {}", path))?;
+        if !passphrase.is_empty() {
+            builder = builder.password(passphrase);
+        }
+        builder
+            .build()
+            .with_context(|| format!("Failed to derive {} from the seed phrase", path))
+    };
+
+    let evm = derive(&derivation.evm_path())?;
+    let btc = derive(&derivation.btc_path())?.to_bytes();
+
+    // PSBT key origins name the seed's master key by its fingerprint
+    let seed = Mnemonic::<English>::new_from_phrase(phrase.trim())
+        .and_then(|mnemonic| mnemonic.to_seed((!passphrase.is_empty()).then_some(passphrase)))
+        .context("Failed to read the seed phrase")?;
+    let master = Xpriv::new_master(bitcoin::NetworkKind::Main, &seed)?;
+    let btc_path = DerivationPath::from_str(&derivation.btc_path())?;
+
+    Ok(WalletKeys {
+        btc: secret_key(btc.as_slice())?,
+        evm,
+        derivation: Some(derivation.clone()),
+        btc_origin: Some((master.fingerprint(&Secp256k1::new()), btc_path)),
+    })
+}
+
+fn decrypt_keystore(path: &Path, label: &str) -> Result<PrivateKeySigner> {
+    let password = rpassword::prompt_password(format!("{} keystore password ({}): ", label, path.display()))
+        .context("Failed to read the keystore password")?;
+
+    PrivateKeySigner::decrypt_keystore(path, password)
+        .with_context(|| format!("Failed to decrypt the {} keystore at {}", label, path.display()))
+}
+
+fn secret_key(bytes: &[u8]) -> Result<SecretKey> {
+    SecretKey::from_slice(bytes).map_err(|e| anyhow!("invalid secp256k1 key: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Address, CompressedPublicKey};
+
+    use super::*;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn derives_the_standard_test_vectors() {
+        let derivation = Derivation { index: 0, btc_purpose: 84, btc_coin_type: 0 };
+        let keys = from_mnemonic(PHRASE, "", &derivation).unwrap();
+
+        // m/44'/60'/0'/0/0
+        assert_eq!(
+            keys.evm.address().to_string(),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+
+        // m/84'/0'/0'/0/0, the first receive address of the BIP84 test vectors
+        let public_key = CompressedPublicKey(keys.btc.public_key(&Secp256k1::new()));
+        assert_eq!(
+            Address::p2wpkh(&public_key, bitcoin::Network::Bitcoin).to_string(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+
+        let (fingerprint, path) = keys.btc_origin.unwrap();
+        assert_eq!(fingerprint.to_string(), "73c5da0a");
+        assert_eq!(path.to_string(), "84'/0'/0'/0/0");
+    }
+
+    #[test]
+    fn derives_the_bip86_test_vector() {
+        let derivation = Derivation { index: 0, btc_purpose: 86, btc_coin_type: 0 };
+        let keys = from_mnemonic(PHRASE, "", &derivation).unwrap();
+
+        // m/86'/0'/0'/0/0, the first receive address of the BIP86 test vectors
+        let public_key = bitcoin::PublicKey::new(keys.btc.public_key(&Secp256k1::new()));
+        let address = derivation.btc_script().address(&public_key, bitcoin::Network::Bitcoin).unwrap();
+        assert_eq!(
+            address.to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(keys.btc_origin.unwrap().1.to_string(), "86'/0'/0'/0/0");
+    }
+
+    #[test]
+    fn paths_follow_the_index_purpose_and_coin_type() {
+        let derivation = Derivation { index: 3, btc_purpose: 84, btc_coin_type: 1 };
+        assert_eq!(derivation.evm_path(), "m/44'/60'/0'/0/3");
+        assert_eq!(derivation.btc_path(), "m/84'/1'/0'/0/3");
+        assert_eq!(derivation.btc_script(), WalletScript::P2wpkh);
+
+        let derivation = Derivation { btc_purpose: 86, ..derivation };
+        assert_eq!(derivation.btc_path(), "m/86'/1'/0'/0/3");
+        assert_eq!(derivation.btc_script(), WalletScript::P2tr);
+    }
+}
@@ -1,6 +1,7 @@
 use std::error::Error;
 use std::panic;
-use clap::{Arg, Command};
+use std::time::Duration;
+use clap::{Arg, ArgAction, Command};
 use ratatui::{
     backend::CrosstermBackend,
     Terminal,
@@ -11,12 +12,18 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 mod app;
+mod cli;
 mod ui;
 pub mod service;
 mod context;
 mod config;
+mod journal;
+mod keystore;
+mod swap;
 use app::App;
 
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 
 fn restore_terminal() -> Result<(), Box<dyn Error>> {
     disable_raw_mode()?;
@@ -47,16 +54,88 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .value_name("CONFIG")
                 .help("path to config file")
                 .required(true),
+            Arg::new("keystore")
+                .long("keystore")
+                .value_name("FILE")
+                .help("Ethereum JSON v3 keystore holding the EVM key")
+                .global(true),
+            Arg::new("btc_keystore")
+                .long("btc-keystore")
+                .value_name("FILE")
+                .help("keystore holding the BTC key, defaults to the EVM key")
+                .requires("keystore")
+                .global(true),
+            Arg::new("mnemonic")
+                .long("mnemonic")
+                .help("prompt for a BIP39 seed phrase and derive both keys from it")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("keystore")
+                .global(true),
+            Arg::new("account_index")
+                .long("account-index")
+                .value_name("INDEX")
+                .help("address index of the keys derived from a seed phrase")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("0")
+                .global(true),
+            Arg::new("btc_purpose")
+                .long("btc-purpose")
+                .value_name("PURPOSE")
+                .help("BIP84 or BIP86 derivation path for the Bitcoin key")
+                .value_parser(["84", "86"])
+                .default_value("84")
+                .global(true),
+            Arg::new("signer_socket")
+                .long("signer-socket")
+                .value_name("PATH")
+                .help("sign through the signer daemon listening on this Unix socket")
+                .conflicts_with_all(["keystore", "mnemonic"])
+                .global(true),
+            Arg::new("allow_env_keys")
+                .long("allow-env-keys")
+                .help("read keys from MNEMONIC, or PRIV_KEY and BTC_PRIV_KEY, when no keystore is given")
+                .action(ArgAction::SetTrue)
+                .global(true),
+            Arg::new("json")
+                .long("json")
+                .help("print subcommand results as JSON")
+                .action(ArgAction::SetTrue)
+                .global(true),
         ])
+        .subcommands(cli::subcommands())
         .get_matches();
     
     
     let network_name = matches.get_one::<String>("network").expect("error retrieving network");
     let config_file_path = matches.get_one::<String>("config").expect("Config file path is required");
     
+    // keystores prompt for their password, so keys are loaded before the TUI owns the terminal
+    let key_source = keystore::KeySource::from_matches(&matches, context::btc_network(network_name))?;
+    
+    // the stand-in daemon serves local keys and needs neither the orderbook nor the terminal
+    if let Some(("signer-daemon", sub_matches)) = matches.subcommand() {
+        std::process::exit(cli::signer_daemon(sub_matches, &key_source));
+    }
+    let wallet = keystore::Wallet::open(&key_source)?;
+    
+    // subcommands run headless and never touch the terminal
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        let config = config::Config::from_file(config_file_path)?;
+        let json = matches.get_flag("json");
+        let code = match context::AppContext::new(network_name, config.get_network(network_name)?, wallet) {
+            Ok(context) => cli::run(name, sub_matches, context, json),
+            Err(e) => cli::report(e.into(), json),
+        };
+        std::process::exit(code);
+    }
+    
     // Set up panic hook before touching the terminal
     let original_hook = panic::take_hook();
     panic::set_hook(Box::new(move |panic_info| {
+        // background task panics are caught by the task, and printing them would draw over the TUI
+        if std::thread::current().name() != Some("main") {
+            return;
+        }
         let _ = restore_terminal();
         // call the original panic handler after restoring terminal
         original_hook(panic_info);
@@ -72,13 +151,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Run the app inside a result-returning function for clean error handling
     let run_app_result = (|| -> Result<Option<String>, Box<dyn Error>> {
         let config = config::Config::from_file(config_file_path)?;
-        let mut app = App::new(network_name, config);
+        let mut app = App::new(network_name, config, wallet)?;
         
         while !app.should_quit {
             terminal.draw(|f| app.draw(f))?;
-            if let Event::Key(key) = event::read()? {
-                app.handle_key(key);
+            // wake up at least every tick so background results and spinners are redrawn
+            if event::poll(TICK_RATE)? {
+                if let Event::Key(key) = event::read()? {
+                    app.handle_key(key);
+                }
             }
+            app.tick();
         }
         
         Ok(app.get_final_message())
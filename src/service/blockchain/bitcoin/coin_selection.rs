@@ -0,0 +1,387 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use bitcoin::{OutPoint, Script, Txid};
+use serde::{Deserialize, Serialize};
+
+use super::fee;
+use super::htlc_handler::UTXO;
+use super::wallet::WalletScript;
+
+/// Branch and bound gives up after visiting this many nodes and falls back to largest first.
+const BNB_MAX_TRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    /// Looks for inputs that pay the target without change, falling back to largest first.
+    #[default]
+    BranchAndBound,
+    /// Spends the largest UTXOs first, which keeps the input count low.
+    LargestFirst,
+}
+
+/// Which of the wallet's UTXOs may fund a transaction and how they are picked.
+#[derive(Debug, Clone, Default)]
+pub struct CoinSelector {
+    strategy: SelectionStrategy,
+    wallet_script: WalletScript,
+    confirmed_only: bool,
+    locked: HashSet<OutPoint>,
+}
+
+/// UTXOs picked to fund a transaction, with what they leave for the fee and change.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub utxos: Vec<UTXO>,
+    pub fee: u64,
+    /// Change back to the wallet, `None` when the excess was too small and went to the fee.
+    pub change: Option<u64>,
+}
+
+impl CoinSelector {
+    pub fn new(strategy: SelectionStrategy) -> Self {
+        CoinSelector {
+            strategy,
+            ..Default::default()
+        }
+    }
+
+    /// Skip UTXOs whose transaction has not been mined yet.
+    pub fn confirmed_only(mut self, confirmed_only: bool) -> Self {
+        self.confirmed_only = confirmed_only;
+        self
+    }
+
+    /// Script type of the wallet's UTXOs, which sets the fee of each input.
+    pub fn wallet_script(mut self, wallet_script: WalletScript) -> Self {
+        self.wallet_script = wallet_script;
+        self
+    }
+
+    /// Never spend `outpoints`, e.g. inputs of another order's exported funding PSBT.
+    pub fn lock(mut self, outpoints: impl IntoIterator<Item = OutPoint>) -> Self {
+        self.locked.extend(outpoints);
+        self
+    }
+
+    /// Picks UTXOs paying `amount` to `outputs` at `fee_rate` sat/vB, every input spending one
+    /// of the wallet's outputs. Change goes to `change_script` unless it would be dust.
+    pub fn select(
+        &self,
+        utxos: Vec<UTXO>,
+        amount: u64,
+        fee_rate: u64,
+        outputs: &[&Script],
+        change_script: &Script,
+    ) -> Result<Selection> {
+        let with_change: Vec<&Script> = outputs.iter().copied().chain([change_script]).collect();
+        let costs = Costs::new(self.wallet_script, fee_rate, outputs, &with_change);
+
+        // inputs worth less than the fee to spend them only make the transaction more expensive
+        let mut candidates: Vec<UTXO> = utxos
+            .into_iter()
+            .filter(|utxo| !self.confirmed_only || utxo.status.confirmed)
+            .filter(|utxo| !outpoint(utxo).is_some_and(|outpoint| self.locked.contains(&outpoint)))
+            .filter(|utxo| utxo.value > costs.input_fee)
+            .collect();
+        candidates.sort_by_key(|utxo| Reverse(utxo.value));
+
+        if self.strategy == SelectionStrategy::BranchAndBound {
+            if let Some(selection) = branch_and_bound(&candidates, amount, &costs, outputs) {
+                return Ok(selection);
+            }
+        }
+        largest_first(candidates, amount, &costs, outputs, &with_change, change_script)
+    }
+}
+
+/// Fee contributions of the parts of a funding transaction at one fee rate.
+struct Costs {
+    wallet_script: WalletScript,
+    fee_rate: u64,
+    /// Fee for spending one of the wallet's outputs.
+    input_fee: u64,
+    /// Fee for everything but the inputs and the change output.
+    base_fee: u64,
+    /// Fee for adding a change output now and spending it later.
+    cost_of_change: u64,
+}
+
+impl Costs {
+    fn new(wallet_script: WalletScript, fee_rate: u64, outputs: &[&Script], with_change: &[&Script]) -> Self {
+        let one_input = fee::wallet_spend_weight(wallet_script, 1, outputs);
+        let input_weight = fee::wallet_spend_weight(wallet_script, 2, outputs) - one_input;
+        let change_weight = fee::wallet_spend_weight(wallet_script, 1, with_change) - one_input;
+
+        let input_fee = fee::fee_for(input_weight, fee_rate);
+        Costs {
+            wallet_script,
+            fee_rate,
+            input_fee,
+            base_fee: fee::fee_for(one_input - input_weight, fee_rate),
+            cost_of_change: fee::fee_for(change_weight, fee_rate) + input_fee,
+        }
+    }
+
+    /// Fee for a whole transaction spending `inputs` to `outputs`.
+    fn fee(&self, inputs: usize, outputs: &[&Script]) -> u64 {
+        fee::fee_for(fee::wallet_spend_weight(self.wallet_script, inputs, outputs), self.fee_rate)
+    }
+}
+
+fn outpoint(utxo: &UTXO) -> Option<OutPoint> {
+    Some(OutPoint {
+        txid: Txid::from_str(&utxo.txid).ok()?,
+        vout: utxo.vout,
+    })
+}
+
+/// Searches for inputs whose value after their own fee lands between the target and the
+/// target plus the cost of change, so no change output is needed. The excess goes to the fee.
+fn branch_and_bound(
+    candidates: &[UTXO],
+    amount: u64,
+    costs: &Costs,
+    outputs: &[&Script],
+) -> Option<Selection> {
+    let effective: Vec<u64> = candidates.iter().map(|utxo| utxo.value - costs.input_fee).collect();
+    let mut search = BnbSearch {
+        effective: &effective,
+        target: amount + costs.base_fee,
+        upper_bound: amount + costs.base_fee + costs.cost_of_change,
+        tries: 0,
+        selected: Vec::new(),
+        best: None,
+    };
+    search.run(0, 0, effective.iter().sum());
+
+    let (_, indexes) = search.best?;
+    let utxos: Vec<UTXO> = indexes.into_iter().map(|i| candidates[i].clone()).collect();
+    let total: u64 = utxos.iter().map(|utxo| utxo.value).sum();
+
+    // per input rounding can undercount the fee of the whole transaction by a few sats
+    let fee = costs.fee(utxos.len(), outputs);
+    if total < amount + fee {
+        return None;
+    }
+    Some(Selection {
+        utxos,
+        fee: total - amount,
+        change: None,
+    })
+}
+
+struct BnbSearch<'a> {
+    /// Candidate values minus their input fee, largest first.
+    effective: &'a [u64],
+    target: u64,
+    upper_bound: u64,
+    tries: usize,
+    selected: Vec<usize>,
+    /// Excess over the target and the indexes of the best selection so far.
+    best: Option<(u64, Vec<usize>)>,
+}
+
+impl BnbSearch<'_> {
+    fn run(&mut self, index: usize, value: u64, remaining: u64) {
+        if self.tries >= BNB_MAX_TRIES || self.best.as_ref().is_some_and(|(excess, _)| *excess == 0) {
+            return;
+        }
+        self.tries += 1;
+
+        if value > self.upper_bound {
+            return;
+        }
+        if value >= self.target {
+            let excess = value - self.target;
+            if self.best.as_ref().is_none_or(|(best, _)| excess < *best) {
+                self.best = Some((excess, self.selected.clone()));
+            }
+            return;
+        }
+        if index == self.effective.len() || value + remaining < self.target {
+            return;
+        }
+
+        let candidate = self.effective[index];
+        self.selected.push(index);
+        self.run(index + 1, value + candidate, remaining - candidate);
+        self.selected.pop();
+        self.run(index + 1, value, remaining - candidate);
+    }
+}
+
+/// Spends candidates, largest first, until they cover the amount and the fee.
+fn largest_first(
+    candidates: Vec<UTXO>,
+    amount: u64,
+    costs: &Costs,
+    outputs: &[&Script],
+    with_change: &[&Script],
+    change_script: &Script,
+) -> Result<Selection> {
+    let fee_for = |inputs: usize, outputs: &[&Script]| costs.fee(inputs, outputs);
+
+    let mut utxos = Vec::new();
+    let mut total = 0;
+    for utxo in candidates {
+        total += utxo.value;
+        utxos.push(utxo);
+        if total >= amount + fee_for(utxos.len(), with_change) {
+            break;
+        }
+    }
+
+    let fee = fee_for(utxos.len(), with_change);
+    if total >= amount + fee && !fee::is_dust(total - amount - fee, change_script) {
+        return Ok(Selection {
+            utxos,
+            fee,
+            change: Some(total - amount - fee),
+        });
+    }
+
+    // too little is left over for a change output, so it goes to the fee
+    let fee = fee_for(utxos.len(), outputs);
+    if total >= amount + fee {
+        return Ok(Selection {
+            utxos,
+            fee: total - amount,
+            change: None,
+        });
+    }
+
+    Err(anyhow!(
+        "Not enough spendable funds, need {} sats plus {} sats fee and have {}",
+        amount,
+        fee,
+        total
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{hashes::Hash, ScriptBuf, WPubkeyHash};
+
+    use super::*;
+    use crate::service::blockchain::bitcoin::htlc_handler::Status;
+
+    const FEE_RATE: u64 = 2;
+
+    fn utxo(n: u8, value: u64, confirmed: bool) -> UTXO {
+        UTXO {
+            txid: format!("{:064x}", n),
+            vout: 0,
+            status: Status {
+                confirmed,
+                block_height: 0,
+                block_hash: String::new(),
+                block_time: 0,
+            },
+            value,
+        }
+    }
+
+    fn script(byte: u8) -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([byte; 20]))
+    }
+
+    fn select(selector: &CoinSelector, utxos: Vec<UTXO>, amount: u64) -> Result<Selection> {
+        let (output, change) = (script(1), script(2));
+        selector.select(utxos, amount, FEE_RATE, &[&output], &change)
+    }
+
+    fn fee(inputs: usize, outputs: usize) -> u64 {
+        let scripts = [script(1), script(2)];
+        let outputs: Vec<&Script> = scripts.iter().take(outputs).map(|s| s.as_script()).collect();
+        fee::fee_for(fee::wallet_spend_weight(WalletScript::P2wpkh, inputs, &outputs), FEE_RATE)
+    }
+
+    fn txids(selection: &Selection) -> Vec<&str> {
+        selection.utxos.iter().map(|utxo| utxo.txid.as_str()).collect()
+    }
+
+    #[test]
+    fn branch_and_bound_finds_exact_match_without_change() {
+        let (output, change) = (script(1), script(2));
+        let costs = Costs::new(WalletScript::P2wpkh, FEE_RATE, &[&output], &[&output, &change]);
+        let amount = 50_000;
+        // per input fees round up, so this can pay a sat more than the whole transaction needs
+        let exact = amount + costs.base_fee + costs.input_fee;
+        let utxos = vec![utxo(1, 1_000_000, true), utxo(2, exact, true), utxo(3, 20_000, true)];
+
+        let selection = select(&CoinSelector::default(), utxos, amount).unwrap();
+        assert_eq!(txids(&selection), [format!("{:064x}", 2)]);
+        assert_eq!(selection.change, None);
+        assert_eq!(selection.fee, exact - amount);
+    }
+
+    #[test]
+    fn largest_first_returns_change() {
+        let selector = CoinSelector::new(SelectionStrategy::LargestFirst);
+        let utxos = vec![utxo(1, 40_000, true), utxo(2, 100_000, true)];
+
+        let selection = select(&selector, utxos, 50_000).unwrap();
+        assert_eq!(txids(&selection), [format!("{:064x}", 2)]);
+        assert_eq!(selection.fee, fee(1, 2));
+        assert_eq!(selection.change, Some(100_000 - 50_000 - fee(1, 2)));
+    }
+
+    #[test]
+    fn taproot_inputs_are_priced_as_key_path_spends() {
+        let selector = CoinSelector::new(SelectionStrategy::LargestFirst).wallet_script(WalletScript::P2tr);
+        let (output, change) = (script(1), script(2));
+        let key_path_fee = fee::fee_for(fee::wallet_spend_weight(WalletScript::P2tr, 1, &[&output, &change]), FEE_RATE);
+        assert!(key_path_fee < fee(1, 2));
+
+        let selection = select(&selector, vec![utxo(1, 100_000, true)], 50_000).unwrap();
+        assert_eq!(selection.fee, key_path_fee);
+        assert_eq!(selection.change, Some(100_000 - 50_000 - key_path_fee));
+    }
+
+    #[test]
+    fn dust_change_goes_to_the_fee() {
+        let selector = CoinSelector::new(SelectionStrategy::LargestFirst);
+        let amount = 50_000;
+        let total = amount + fee(1, 2) + 100;
+        assert!(fee::is_dust(100, &script(2)));
+
+        let selection = select(&selector, vec![utxo(1, total, true)], amount).unwrap();
+        assert_eq!(selection.change, None);
+        assert_eq!(selection.fee, total - amount);
+    }
+
+    #[test]
+    fn confirmed_only_skips_unconfirmed_utxos() {
+        let utxos = vec![utxo(1, 100_000, true), utxo(2, 200_000, false)];
+
+        let selector = CoinSelector::new(SelectionStrategy::LargestFirst);
+        let selection = select(&selector, utxos.clone(), 50_000).unwrap();
+        assert_eq!(txids(&selection), [format!("{:064x}", 2)]);
+
+        let selection = select(&selector.confirmed_only(true), utxos, 50_000).unwrap();
+        assert_eq!(txids(&selection), [format!("{:064x}", 1)]);
+    }
+
+    #[test]
+    fn locked_outpoints_are_not_spent() {
+        let utxos = vec![utxo(1, 100_000, true), utxo(2, 200_000, true)];
+        let locked = outpoint(&utxos[1]).unwrap();
+
+        let selector = CoinSelector::new(SelectionStrategy::LargestFirst).lock([locked]);
+        let selection = select(&selector, utxos, 50_000).unwrap();
+        assert_eq!(txids(&selection), [format!("{:064x}", 1)]);
+    }
+
+    #[test]
+    fn insufficient_funds_is_an_error() {
+        let utxos = vec![utxo(1, 10_000, true), utxo(2, 20_000, true)];
+
+        for strategy in [SelectionStrategy::BranchAndBound, SelectionStrategy::LargestFirst] {
+            assert!(select(&CoinSelector::new(strategy), utxos.clone(), 50_000).is_err());
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use bitcoin::{
+    transaction::{predict_weight, InputWeightPrediction},
+    Amount, Script, Weight,
+};
+use serde::{Deserialize, Serialize};
+
+use super::wallet::WalletScript;
+
+/// Used when the indexer has no estimates, which is the case on an idle regtest chain.
+pub const FALLBACK_FEE_RATE: u64 = 3;
+
+/// Serialized BIP340 signature followed by its `SIGHASH_ALL` byte.
+const SCHNORR_SIGNATURE_LEN: usize = 65;
+
+/// How soon a transaction should confirm, picked from the indexer's estimates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeePriority {
+    Fastest,
+    #[default]
+    Fast,
+    Normal,
+    Economy,
+}
+
+impl FeePriority {
+    /// Confirmation target in blocks.
+    pub fn target(&self) -> u16 {
+        match self {
+            FeePriority::Fastest => 1,
+            FeePriority::Fast => 3,
+            FeePriority::Normal => 6,
+            FeePriority::Economy => 144,
+        }
+    }
+
+    /// The next priority, for cycling through them in the UI.
+    pub fn next(&self) -> Self {
+        match self {
+            FeePriority::Fastest => FeePriority::Fast,
+            FeePriority::Fast => FeePriority::Normal,
+            FeePriority::Normal => FeePriority::Economy,
+            FeePriority::Economy => FeePriority::Fastest,
+        }
+    }
+}
+
+impl fmt::Display for FeePriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FeePriority::Fastest => "fastest",
+            FeePriority::Fast => "fast",
+            FeePriority::Normal => "normal",
+            FeePriority::Economy => "economy",
+        };
+        write!(f, "{} (~{} blocks)", name, self.target())
+    }
+}
+
+/// Esplora's `/fee-estimates`, sat/vB keyed by confirmation target in blocks.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct FeeEstimates(HashMap<String, f64>);
+
+impl FeeEstimates {
+    /// Fee rate in sat/vB for `priority`. Esplora only estimates some targets, so the closest
+    /// target that confirms no later than the requested one is used.
+    pub fn rate(&self, priority: FeePriority) -> u64 {
+        let estimates: Vec<(u16, f64)> = self
+            .0
+            .iter()
+            .filter_map(|(target, rate)| Some((target.parse().ok()?, *rate)))
+            .collect();
+
+        let rate = estimates
+            .iter()
+            .filter(|(target, _)| *target <= priority.target())
+            .max_by_key(|(target, _)| *target)
+            .or_else(|| estimates.iter().min_by_key(|(target, _)| *target))
+            .map(|(_, rate)| *rate);
+
+        match rate {
+            Some(rate) => (rate.ceil() as u64).max(1),
+            None => FALLBACK_FEE_RATE,
+        }
+    }
+}
+
+/// Fee for a transaction of `weight` at `fee_rate` sat/vB.
+pub fn fee_for(weight: Weight, fee_rate: u64) -> u64 {
+    weight.to_vbytes_ceil() * fee_rate
+}
+
+/// Weight of a transaction spending `inputs` of the wallet's `script` outputs to `outputs`.
+pub fn wallet_spend_weight(script: WalletScript, inputs: usize, outputs: &[&Script]) -> Weight {
+    predict_weight(
+        std::iter::repeat_n(script.input_weight(), inputs),
+        outputs.iter().map(|script| script.len()),
+    )
+}
+
+/// Weight of a transaction spending one HTLC leaf to `output`. `witness_stack` is the leaf's
+/// witness from `BitcoinHTLC`, whose signature placeholder is sized as a real signature.
+pub fn leaf_spend_weight(witness_stack: &[Vec<u8>], output: &Script) -> Weight {
+    leaf_spends_weight(1, witness_stack, output)
+}
+
+/// Weight of a transaction spending `inputs` outputs of the same HTLC leaf to `output`.
+pub fn leaf_spends_weight(inputs: usize, witness_stack: &[Vec<u8>], output: &Script) -> Weight {
+    let element_lengths = witness_stack
+        .iter()
+        .enumerate()
+        .map(|(i, element)| if i == 0 { SCHNORR_SIGNATURE_LEN } else { element.len() });
+    let input = InputWeightPrediction::new(0, element_lengths);
+
+    predict_weight(std::iter::repeat_n(input, inputs), [output.len()])
+}
+
+/// Whether an output of `value` paying to `script` would be rejected as dust.
+pub fn is_dust(value: u64, script: &Script) -> bool {
+    Amount::from_sat(value) < script.minimal_non_dust()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        absolute::LockTime, hashes::Hash, transaction::Version, OutPoint, ScriptBuf, Sequence, Transaction, TxIn,
+        TxOut, WPubkeyHash, Witness,
+    };
+
+    use super::*;
+    use crate::service::blockchain::bitcoin::htlc::BitcoinHTLC;
+
+    fn estimates(rates: &[(&str, f64)]) -> FeeEstimates {
+        FeeEstimates(rates.iter().map(|(target, rate)| (target.to_string(), *rate)).collect())
+    }
+
+    fn p2wpkh_script() -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([7; 20]))
+    }
+
+    fn p2tr_script() -> ScriptBuf {
+        ScriptBuf::new_p2tr_tweaked(bitcoin::key::TweakedPublicKey::dangerous_assume_tweaked(
+            bitcoin::XOnlyPublicKey::from_slice(&[2; 32]).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn rate_uses_the_priority_target() {
+        let estimates = estimates(&[("1", 20.0), ("3", 10.2), ("6", 5.0), ("144", 1.0)]);
+        assert_eq!(estimates.rate(FeePriority::Fastest), 20);
+        // rates are rounded up to whole sat/vB
+        assert_eq!(estimates.rate(FeePriority::Fast), 11);
+        assert_eq!(estimates.rate(FeePriority::Economy), 1);
+    }
+
+    #[test]
+    fn rate_falls_back_to_the_closest_faster_target() {
+        let estimates = estimates(&[("1", 20.0), ("2", 15.0), ("25", 2.0)]);
+        assert_eq!(estimates.rate(FeePriority::Fast), 15);
+        assert_eq!(estimates.rate(FeePriority::Normal), 15);
+        assert_eq!(estimates.rate(FeePriority::Economy), 2);
+    }
+
+    #[test]
+    fn rate_without_a_faster_target_uses_the_fastest_one() {
+        let estimates = estimates(&[("6", 5.0), ("144", 1.0)]);
+        assert_eq!(estimates.rate(FeePriority::Fastest), 5);
+    }
+
+    #[test]
+    fn rate_without_estimates_uses_the_fallback() {
+        assert_eq!(FeeEstimates::default().rate(FeePriority::Fast), FALLBACK_FEE_RATE);
+        assert_eq!(estimates(&[("3", 0.2)]).rate(FeePriority::Fast), 1);
+    }
+
+    #[test]
+    fn p2wpkh_spend_weight_matches_known_sizes() {
+        let script = p2wpkh_script();
+        // one P2WPKH input paying a P2WPKH output and change is the familiar 141 vB transaction
+        let weight = wallet_spend_weight(WalletScript::P2wpkh, 1, &[&script, &script]);
+        assert_eq!(weight.to_vbytes_ceil(), 141);
+        // every further input adds 68 vB
+        let weight = wallet_spend_weight(WalletScript::P2wpkh, 2, &[&script, &script]);
+        assert_eq!(weight.to_vbytes_ceil(), 209);
+    }
+
+    #[test]
+    fn p2tr_spend_weight_matches_known_sizes() {
+        let script = p2tr_script();
+        // one key path input paying a P2TR output and change
+        let weight = wallet_spend_weight(WalletScript::P2tr, 1, &[&script, &script]);
+        assert_eq!(weight.to_vbytes_ceil(), 154);
+        // every further input adds 57.5 vB
+        let weight = wallet_spend_weight(WalletScript::P2tr, 3, &[&script, &script]);
+        assert_eq!(weight.to_vbytes_ceil(), 269);
+    }
+
+    #[test]
+    fn leaf_spend_weight_matches_the_signed_transaction() {
+        let htlc = BitcoinHTLC::new(
+            vec![1; 32],
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5".to_string(),
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9".to_string(),
+            144,
+            bitcoin::Network::Regtest,
+        )
+        .unwrap();
+        let witness_stack = htlc.refund().unwrap();
+        let output = p2wpkh_script();
+
+        // the signed refund, with a Schnorr signature and its sighash byte in place of the placeholder
+        let mut witness = Witness::new();
+        witness.push([0u8; SCHNORR_SIGNATURE_LEN]);
+        for element in witness_stack.iter().skip(1) {
+            witness.push(element);
+        }
+        let input = TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::from_height(144),
+            witness,
+        };
+        let mut tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![input],
+            output: vec![TxOut { value: Amount::from_sat(10_000), script_pubkey: output.clone() }],
+        };
+
+        assert_eq!(leaf_spend_weight(&witness_stack, &output), tx.weight());
+
+        tx.input.push(tx.input[0].clone());
+        assert_eq!(leaf_spends_weight(2, &witness_stack, &output), tx.weight());
+    }
+
+    #[test]
+    fn dust_limits_follow_the_output_type() {
+        let p2wpkh = p2wpkh_script();
+        assert!(is_dust(293, &p2wpkh));
+        assert!(!is_dust(294, &p2wpkh));
+
+        let p2tr = p2tr_script();
+        assert!(is_dust(329, &p2tr));
+        assert!(!is_dust(330, &p2tr));
+    }
+}
@@ -55,6 +55,10 @@ impl BitcoinHTLC {
         })
     }
 
+    pub fn timelock(&self) -> i64 {
+        self.timelock
+    }
+
     fn construct_taproot(&self) -> Result<TaprootBuilder> {
         let redeem_leaf = redeem_leaf(&self.secret_hash, &self.redeemer_pubkey).expect("error building redeem leaf");
         let refund_leaf = refund_leaf(self.timelock, &self.initiator_pubkey).expect("error building refund leaf");
@@ -109,7 +113,7 @@ impl BitcoinHTLC {
                 (redeem, cb_bytes.clone())
             },
             Leaf::Refund => {
-                let refund = refund_leaf(self.timelock, &self.redeemer_pubkey)?;
+                let refund = refund_leaf(self.timelock, &self.initiator_pubkey)?;
                 
                 let ctrlblck = taproot_script_tree.control_block(&(refund.clone(), LeafVersion::TapScript)).unwrap();
                 
@@ -152,7 +156,6 @@ impl BitcoinHTLC {
         Ok(witness_data)
     }
     
-    #[allow(dead_code)]
     pub fn refund(&self) -> Result<Vec<Vec<u8>>> {
         let mut witness_data: Vec<Vec<u8>> = Vec::new();
         let sig_data = hex::decode("000000000000")?;
@@ -1,11 +1,20 @@
 use anyhow::{anyhow, Context, Result};
 use bitcoin::{
-    absolute::LockTime, address::Address, key::{Keypair, Secp256k1}, secp256k1::{All, Message}, sighash::SighashCache, taproot::LeafVersion, transaction::Version, Amount, CompressedPublicKey, EcdsaSighashType, OutPoint, PrivateKey, PublicKey, Script, ScriptBuf, Sequence, TapLeafHash, TapSighashType, Transaction, TxIn, TxOut, Txid, Witness
+    absolute::LockTime, address::Address, psbt::Psbt, secp256k1::Message, sighash::{Prevouts, SighashCache}, taproot::{ControlBlock, LeafVersion}, transaction::Version, Amount, EcdsaSighashType, OutPoint, Script, ScriptBuf, Sequence, TapLeafHash, TapSighashType, Transaction, TxIn, TxOut, Txid, Weight, Witness
 };
 
 use serde::Deserialize;
 use std::{str::FromStr, time::Duration};
 
+use super::coin_selection::CoinSelector;
+use super::fee::{self, FeeEstimates, FeePriority};
+use super::htlc::{BitcoinHTLC, Leaf};
+use super::wallet::WalletScript;
+use crate::service::signer::WalletSigner;
+
+/// Bitcoin Core's default incremental relay fee, which a replacement must add per vbyte.
+const MIN_RELAY_FEE_RATE: u64 = 1;
+
 pub struct SimpleIndexer {
     client: reqwest::Client,
     url: String
@@ -31,23 +40,45 @@ impl SimpleIndexer {
         Ok(resp)
     }
 
-    pub async fn get_utxos_for_amount(&self, address:&str, amount: i64) -> Result<Vec<UTXO>> {
-        let utxos = self.get_utxos(address).await?;
-        let mut filtered_utxos: Vec<UTXO> = Vec::new();
-        let mut total = 0;
+    pub async fn get_tip_height(&self) -> Result<u64> {
+        let url = format!("{}/blocks/tip/height", &self.url);
 
-        for utxo in utxos {
-            total += utxo.value as i64;
-            filtered_utxos.push(utxo);
-            if total == amount {
-                return Ok(filtered_utxos);
-            }
-        }
+        let response = self.client.get(url).send().await?;
+        let height = response.text().await?
+            .trim()
+            .parse::<u64>()
+            .context("failed to parse tip height")?;
 
-        if total < amount {
-            return Err(anyhow!("Not enough funds in UTXOs"));
-        }
-        Ok(filtered_utxos)
+        Ok(height)
+    }
+
+    /// Transactions paying to or spending from `address`, unconfirmed ones first.
+    pub async fn get_address_txs(&self, address: &str) -> Result<Vec<IndexedTx>> {
+        let url = format!("{}/address/{}/txs", &self.url, address);
+
+        let response = self.client.get(url).send().await?;
+        let txs = response.json::<Vec<IndexedTx>>().await?;
+
+        Ok(txs)
+    }
+
+    pub async fn get_tx(&self, txid: &str) -> Result<Transaction> {
+        let url = format!("{}/tx/{}/hex", &self.url, txid);
+
+        let response = self.client.get(url).send().await?;
+        let bytes = hex::decode(response.text().await?.trim()).context("transaction is not valid hex")?;
+
+        Ok(bitcoin::consensus::deserialize(&bytes)?)
+    }
+
+    pub async fn get_fee_estimates(&self) -> Result<FeeEstimates> {
+        let url = format!("{}/fee-estimates", &self.url);
+
+        let response = self.client.get(url).send().await?;
+        let estimates = response.json::<FeeEstimates>().await
+            .context("failed to parse fee estimates")?;
+
+        Ok(estimates)
     }
 
     pub async fn submit_tx(&self, tx: &bitcoin::Transaction) -> Result<String> {
@@ -70,7 +101,7 @@ impl SimpleIndexer {
                         if resp.status().is_success() {
                             return Ok(resp.text().await?.to_string());
                         } else {
-                            let err_msg = resp.text().await.map_err(|e| e)?;
+                            let err_msg = resp.text().await?;
                             last_error = Some(anyhow!("req failed : {:#?}", err_msg));
                         }
                     },
@@ -95,7 +126,7 @@ impl SimpleIndexer {
 pub struct HtlcHandler {
     network: bitcoin::Network,
     indexer: SimpleIndexer,
-    secp: Secp256k1<All>
+    wallet_script: WalletScript,
 }
 
 impl HtlcHandler {
@@ -103,37 +134,57 @@ impl HtlcHandler {
         Ok(Self{
             network,
             indexer: SimpleIndexer::new(indexer_url)?,
-            secp: Secp256k1::new(),
+            wallet_script: WalletScript::default(),
         })
     }
 
+    /// Script type of the wallet's own outputs, P2WPKH unless set.
+    pub fn with_wallet_script(mut self, wallet_script: WalletScript) -> Self {
+        self.wallet_script = wallet_script;
+        self
+    }
+
     pub async fn broadcast_tx(&self, tx: &Transaction) -> Result<String> {
         let tx_id = self.indexer.submit_tx(tx).await.context("failed to broadcast transaction")?;
         Ok(tx_id)
     }
 
-    pub fn get_btc_address_for_priv_key(&self, private_key: PrivateKey) -> Result<String> {
-        let public_key = PublicKey::from_private_key(&self.secp, &private_key);
-        let compressed_pubkey = CompressedPublicKey::try_from(public_key)?;
-        let addr = Address::p2wpkh(&compressed_pubkey, self.network).to_string();
+    /// Fee rate in sat/vB for `priority` from the indexer's estimates.
+    pub async fn fee_rate(&self, priority: FeePriority) -> Result<u64> {
+        let estimates = self.indexer.get_fee_estimates().await?;
+        Ok(estimates.rate(priority))
+    }
+
+    pub fn get_btc_address(&self, signer: &dyn WalletSigner) -> Result<String> {
+        let addr = self.wallet_script.address(&signer.btc_public_key(), self.network)?.to_string();
         Ok(addr)
     }
 
 
-    pub fn initaite_htlc(&self, private_key: PrivateKey, htlc_addr: bitcoin::Address, amount: i64) -> Result<Transaction> {
-        let public_key = PublicKey::from_private_key(&self.secp, &private_key);
-        let compressed_pubkey = CompressedPublicKey::try_from(public_key).unwrap();
-        let sender_address = Address::p2wpkh(&compressed_pubkey, self.network);
+    /// Unsigned transaction paying `amount` from the signer's address to the HTLC at
+    /// `fee_rate` sat/vB, funded by the UTXOs `selector` picks, along with the outputs its
+    /// inputs spend.
+    async fn build_funding_tx(
+        &self,
+        signer: &dyn WalletSigner,
+        htlc_addr: &Address,
+        amount: i64,
+        fee_rate: u64,
+        selector: &CoinSelector,
+    ) -> Result<(Transaction, Vec<TxOut>)> {
+        let sender_address = self.wallet_script.address(&signer.btc_public_key(), self.network)?;
+        let utxos = self.indexer.get_utxos(&sender_address.to_string()).await?;
 
-        let runtime =
-            tokio::runtime::Runtime::new().map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+        let amount = amount as u64;
+        let htlc_script = htlc_addr.script_pubkey();
+        let change_script = sender_address.script_pubkey();
 
-        let utxos = runtime.block_on(self.indexer.get_utxos_for_amount(&sender_address.to_string(), amount))?;
+        let selection = selector.select(utxos, amount, fee_rate, &[htlc_script.as_script()], &change_script)?;
 
-        // Create inputs and track values
+        // Create inputs and track the outputs they spend
         let mut inputs: Vec<TxIn> = Vec::new();
-        let mut input_values: Vec<u64> = Vec::new();
-        for utxo in utxos {
+        let mut prevouts: Vec<TxOut> = Vec::new();
+        for utxo in selection.utxos {
             let txid = Txid::from_str(&utxo.txid)?;
             inputs.push(TxIn {
                 previous_output: OutPoint {
@@ -144,67 +195,104 @@ impl HtlcHandler {
                 sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
                 witness: Witness::new(),
             });
-            input_values.push(utxo.value);
+            prevouts.push(TxOut {
+                value: Amount::from_sat(utxo.value),
+                script_pubkey: change_script.clone(),
+            });
         }
 
-        // Calculate fee and total input amount
-        let fee = 250 * inputs.len() as u64;
-        let total_input: u64 = input_values.iter().sum();
-
-        // Parse HTLC address and create outputs
-        let output = TxOut {
-            value: Amount::from_sat(amount as u64),
-            script_pubkey: htlc_addr.script_pubkey(),
-        };
-
-        let mut outputs = vec![output];
+        let mut outputs = vec![TxOut {
+            value: Amount::from_sat(amount),
+            script_pubkey: htlc_script,
+        }];
 
-        // Add change output if needed
-        if total_input > (amount as u64 + fee) {
+        if let Some(change) = selection.change {
             outputs.push(TxOut {
-                value: Amount::from_sat(total_input - amount as u64 - fee),
-                script_pubkey: sender_address.script_pubkey(),
+                value: Amount::from_sat(change),
+                script_pubkey: change_script,
             });
         }
 
-        // Create unsigned transaction
-        let mut unsigned_tx = Transaction {
+        let unsigned_tx = Transaction {
             version: Version::TWO,
             lock_time: LockTime::ZERO,
             input: inputs,
             output: outputs,
         };
 
-        // Sign each input
-        let mut sighash_cache = SighashCache::new(&mut unsigned_tx);
+        Ok((unsigned_tx, prevouts))
+    }
+
+    pub async fn initaite_htlc(
+        &self,
+        signer: &dyn WalletSigner,
+        htlc_addr: bitcoin::Address,
+        amount: i64,
+        fee_rate: u64,
+        selector: &CoinSelector,
+    ) -> Result<Transaction> {
+        let (unsigned_tx, prevouts) = self.build_funding_tx(signer, &htlc_addr, amount, fee_rate, selector).await?;
+        self.sign_wallet_inputs(unsigned_tx, &prevouts, signer)
+    }
 
-        for i in 0..input_values.len() {
-            // Create the script for this input (p2wpkh)
-            let script_pubkey = ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash()?);
+    /// Signs every input of `tx`, all of them spending the signer's own outputs `prevouts`.
+    fn sign_wallet_inputs(&self, tx: Transaction, prevouts: &[TxOut], signer: &dyn WalletSigner) -> Result<Transaction> {
+        match self.wallet_script {
+            WalletScript::P2wpkh => self.sign_p2wpkh_inputs(tx, prevouts, signer),
+            WalletScript::P2tr => self.sign_key_spend_inputs(tx, prevouts, signer),
+        }
+    }
+
+    /// Signs every input of `tx` through the taproot key path of the signer's BIP86 outputs.
+    fn sign_key_spend_inputs(&self, mut tx: Transaction, prevouts: &[TxOut], signer: &dyn WalletSigner) -> Result<Transaction> {
+        let mut sighash_cache = SighashCache::new(&mut tx);
+
+        // taproot sighashes commit to every output being spent
+        for i in 0..prevouts.len() {
+            let sighash = sighash_cache.taproot_key_spend_signature_hash(
+                i,
+                &Prevouts::All(prevouts),
+                TapSighashType::Default,
+            )?;
+            let signature = signer.sign_btc_key_spend(&Message::from(sighash))?;
 
-            // Get the sighash to sign
+            let signature = bitcoin::taproot::Signature {
+                signature,
+                sighash_type: TapSighashType::Default,
+            };
+            *sighash_cache.witness_mut(i).unwrap() = Witness::p2tr_key_spend(&signature);
+        }
+
+        Ok(sighash_cache.into_transaction().clone())
+    }
+
+    /// Signs every input of `tx`, all of them spending the signer's P2WPKH outputs `prevouts`.
+    fn sign_p2wpkh_inputs(&self, mut tx: Transaction, prevouts: &[TxOut], signer: &dyn WalletSigner) -> Result<Transaction> {
+        let public_key = signer.btc_public_key();
+
+        // Sign each input
+        let mut sighash_cache = SighashCache::new(&mut tx);
+
+        for (i, prevout) in prevouts.iter().enumerate() {
+            // Get the sighash to sign, the prevout is the input's p2wpkh script
             let sighash_type = EcdsaSighashType::All;
             let sighash = sighash_cache.p2wpkh_signature_hash(
                 i,
-                &script_pubkey,
-                Amount::from_sat(input_values[i]),
+                &prevout.script_pubkey,
+                prevout.value,
                 sighash_type,
             )?;
 
             // Sign the sighash
             let msg = Message::from(sighash);
-            let signature = self.secp.sign_ecdsa(&msg, &private_key.inner);
+            let signature = signer.sign_btc_ecdsa(&msg)?;
 
             // Create the signature with sighash type
             let btc_signature = bitcoin::ecdsa::Signature {
                 signature,
                 sighash_type,
             };
-            let pubkey_bytes = public_key.to_bytes();
-            *sighash_cache.witness_mut(i).unwrap() = Witness::p2wpkh(
-                &btc_signature,
-                &bitcoin::secp256k1::PublicKey::from_slice(&pubkey_bytes)?,
-            )
+            *sighash_cache.witness_mut(i).unwrap() = Witness::p2wpkh(&btc_signature, &public_key.inner);
         }
 
         let signed_tx = sighash_cache.into_transaction();
@@ -212,18 +300,69 @@ impl HtlcHandler {
         Ok(signed_tx.clone())
     }
 
-    pub async fn create_redeem_tx(
+    /// Funding transaction as an unsigned PSBT for an external signer. Inputs and the change
+    /// output carry the key's origin so the signer can recognise them as its own.
+    pub async fn initiate_htlc_psbt(
         &self,
-        htlc_addr: Address,
-        witness_stack: Vec<Vec<u8>>,
+        signer: &dyn WalletSigner,
+        htlc_addr: bitcoin::Address,
+        amount: i64,
+        fee_rate: u64,
+        selector: &CoinSelector,
+    ) -> Result<Psbt> {
+        let (unsigned_tx, prevouts) = self.build_funding_tx(signer, &htlc_addr, amount, fee_rate, selector).await?;
+        let public_key = signer.btc_public_key();
+        let (internal_key, _) = public_key.inner.x_only_public_key();
+        let origin = signer.btc_key_origin();
+        let own_script = self.wallet_script.script_pubkey(&public_key)?;
+
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx)?;
+        for (input, prevout) in psbt.inputs.iter_mut().zip(prevouts) {
+            input.witness_utxo = Some(prevout);
+            match self.wallet_script {
+                WalletScript::P2wpkh => {
+                    input.sighash_type = Some(EcdsaSighashType::All.into());
+                    input.bip32_derivation.insert(public_key.inner, origin.clone());
+                }
+                WalletScript::P2tr => {
+                    input.tap_internal_key = Some(internal_key);
+                    input.tap_key_origins.insert(internal_key, (vec![], origin.clone()));
+                }
+            }
+        }
+        for (output, txout) in psbt.outputs.iter_mut().zip(&psbt.unsigned_tx.output) {
+            if txout.script_pubkey != own_script {
+                continue;
+            }
+            match self.wallet_script {
+                WalletScript::P2wpkh => {
+                    output.bip32_derivation.insert(public_key.inner, origin.clone());
+                }
+                WalletScript::P2tr => {
+                    output.tap_internal_key = Some(internal_key);
+                    output.tap_key_origins.insert(internal_key, (vec![], origin.clone()));
+                }
+            }
+        }
+
+        Ok(psbt)
+    }
+
+    /// Unsigned transaction spending the HTLC's funding output to the recipient, or to the
+    /// signer's own address, along with the output it spends. The fee is sized for
+    /// `witness_stack`, the redeem leaf's witness.
+    async fn build_redeem_tx(
+        &self,
+        htlc_addr: &Address,
+        witness_stack: &[Vec<u8>],
         receiver_address: Option<String>,
-        private_key: PrivateKey,
+        signer: &dyn WalletSigner,
         fee_rate: u64,
-    ) -> Result<Transaction> {
+    ) -> Result<(Transaction, Vec<TxOut>)> {
         // Determine the recipient address
         let recipient = match receiver_address {
             Some(addr) => addr,
-            None => self.get_btc_address_for_priv_key(private_key)?
+            None => self.get_btc_address(signer)?
         };
 
         // Fetch UTXOs for the HTLC address
@@ -243,15 +382,13 @@ impl HtlcHandler {
             .require_network(self.network)
             .map_err(|e| anyhow!("Network mismatch: {:?}", e))?;
 
-        // Calculate fee
-        let estimated_tx_size = 200; // vbytes - adjust based on actual tx size
-        let fee = fee_rate * estimated_tx_size;
-
-        // Create output amount after deducting fee
-        let output_value = utxo.value.saturating_sub(fee);
+        // Calculate fee from the size of the signed transaction
+        let recipient_script = btc_addr.script_pubkey();
+        let fee = fee::fee_for(fee::leaf_spend_weight(witness_stack, &recipient_script), fee_rate);
+        let output_value = leaf_spend_output(utxo.value, fee, &recipient_script)?;
 
         // Create the unsigned transaction
-        let mut tx = Transaction {
+        let tx = Transaction {
             version: Version::TWO,
             lock_time: LockTime::ZERO,
             input: vec![TxIn {
@@ -260,56 +397,301 @@ impl HtlcHandler {
                     vout: utxo.vout,
                 },
                 script_sig: ScriptBuf::new(),
-                sequence: Sequence(4294967294),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
                 witness: Witness::new(),
             }],
             output: vec![TxOut {
                 value: Amount::from_sat(output_value),
-                script_pubkey: btc_addr.script_pubkey(),
+                script_pubkey: recipient_script,
             }],
         };
 
+        // Create prevouts for signing
+        let prevouts = vec![TxOut {
+            value: Amount::from_sat(utxo.value),
+            script_pubkey: htlc_addr.script_pubkey(),
+        }];
+
+        Ok((tx, prevouts))
+    }
+
+    pub async fn create_redeem_tx(
+        &self,
+        htlc_addr: Address,
+        witness_stack: Vec<Vec<u8>>,
+        receiver_address: Option<String>,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<Transaction> {
+        let (tx, prevouts) = self.build_redeem_tx(&htlc_addr, &witness_stack, receiver_address, signer, fee_rate).await?;
+
         // Prepare for signing
         let leaf_hash = TapLeafHash::from_script(
             Script::from_bytes(&witness_stack[2]),
             LeafVersion::TapScript,
         );
 
-        // Create prevouts for signing
-        let mut prevouts = Vec::new();
-        prevouts.push(TxOut {
-            value: Amount::from_sat(utxo.value),
-            script_pubkey: htlc_addr.script_pubkey(),
-        });
-
         // Sign the transaction
-        tx = self.sign_and_set_taproot_witness(
+        self.sign_and_set_taproot_witness(
             tx,
             0,
             leaf_hash,
-            private_key,
+            signer,
             TapSighashType::All,
             prevouts,
             witness_stack
-        )?;
+        )
+    }
+
+    /// Redeem transaction as an unsigned PSBT. The secret is not part of it, it is only added
+    /// to the witness when the signed PSBT is finalized.
+    pub async fn create_redeem_psbt(
+        &self,
+        htlc: &BitcoinHTLC,
+        receiver_address: Option<String>,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<Psbt> {
+        let (leaf_script, control_block) = htlc.get_control_block(Leaf::Redeem)?;
+        // sized like the real witness, with the 32 byte secret every order uses
+        let witness_stack = [vec![], vec![0; 32], leaf_script.to_bytes(), control_block.clone()];
+
+        let (tx, prevouts) = self.build_redeem_tx(&htlc.address()?, &witness_stack, receiver_address, signer, fee_rate).await?;
+        leaf_spend_psbt(tx, prevouts, leaf_script, &control_block, signer)
+    }
 
+    /// Unsigned transaction spending the HTLC's refund leaf back to the wallet's address,
+    /// along with the outputs it spends. Every confirmed HTLC output whose timelock expired is
+    /// refunded, so a top-up isn't left behind. Fails if there is none.
+    async fn build_refund_tx(
+        &self,
+        htlc: &BitcoinHTLC,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<(Transaction, Vec<TxOut>)> {
+        let htlc_addr = htlc.address()?;
+        let timelock = htlc.timelock();
+
+        let recipient = Address::from_str(&self.get_btc_address(signer)?)
+            .map_err(|e| anyhow!("Invalid address format: {:?}", e))?
+            .require_network(self.network)
+            .map_err(|e| anyhow!("Network mismatch: {:?}", e))?;
+
+        // Fetch UTXOs for the HTLC address
+        let utxos = self.indexer.get_utxos(&htlc_addr.to_string()).await?;
+        if utxos.is_empty() {
+            return Err(anyhow!("htlc address is not funded"));
+        }
+        let confirmed: Vec<&UTXO> = utxos.iter().filter(|utxo| utxo.status.confirmed).collect();
+        if confirmed.is_empty() {
+            return Err(anyhow!("htlc funding tx {} is not confirmed yet", utxos[0].txid));
+        }
+
+        let tip_height = self.indexer.get_tip_height().await?;
+        let matured: Vec<&UTXO> = confirmed
+            .iter()
+            .copied()
+            .filter(|utxo| csv_matured(tip_height, utxo.status.block_height, timelock))
+            .collect();
+        if matured.is_empty() {
+            let remaining = confirmed
+                .iter()
+                .map(|utxo| (utxo.status.block_height + timelock as u64).saturating_sub(tip_height + 1))
+                .min()
+                .unwrap_or_default();
+            return Err(anyhow!("timelock has not expired yet, {} blocks remaining", remaining));
+        }
+
+        let sequence = Sequence::from_height(
+            u16::try_from(timelock).map_err(|_| anyhow!("timelock {} does not fit a relative locktime", timelock))?,
+        );
+
+        let input = matured
+            .iter()
+            .map(|utxo| {
+                Ok(TxIn {
+                    previous_output: OutPoint {
+                        txid: Txid::from_str(&utxo.txid)?,
+                        vout: utxo.vout,
+                    },
+                    script_sig: ScriptBuf::new(),
+                    sequence,
+                    witness: Witness::new(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let value: u64 = matured.iter().map(|utxo| utxo.value).sum();
+
+        // Calculate fee from the size of the signed transaction
+        let recipient_script = recipient.script_pubkey();
+        let weight = fee::leaf_spends_weight(input.len(), &htlc.refund()?, &recipient_script);
+        let output_value = leaf_spend_output(value, fee::fee_for(weight, fee_rate), &recipient_script)?;
+
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input,
+            output: vec![TxOut {
+                value: Amount::from_sat(output_value),
+                script_pubkey: recipient_script,
+            }],
+        };
+
+        let prevouts = matured
+            .iter()
+            .map(|utxo| TxOut {
+                value: Amount::from_sat(utxo.value),
+                script_pubkey: htlc_addr.script_pubkey(),
+            })
+            .collect();
+
+        Ok((tx, prevouts))
+    }
+
+    /// Builds a transaction spending the HTLC's refund leaf back to the wallet's address.
+    /// Fails if the funding output has not been confirmed for at least `timelock` blocks.
+    pub async fn create_refund_tx(
+        &self,
+        htlc: &BitcoinHTLC,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<Transaction> {
+        let (mut tx, prevouts) = self.build_refund_tx(htlc, signer, fee_rate).await?;
+        let witness_stack = htlc.refund()?;
+
+        let leaf_hash = TapLeafHash::from_script(
+            Script::from_bytes(&witness_stack[1]),
+            LeafVersion::TapScript,
+        );
+
+        for input_index in 0..tx.input.len() {
+            tx = self.sign_and_set_taproot_witness(
+                tx,
+                input_index,
+                leaf_hash,
+                signer,
+                TapSighashType::All,
+                prevouts.clone(),
+                witness_stack.clone()
+            )?;
+        }
         Ok(tx)
     }
 
+    /// Refund transaction as an unsigned PSBT, with the same timelock checks as `create_refund_tx`.
+    pub async fn create_refund_psbt(
+        &self,
+        htlc: &BitcoinHTLC,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<Psbt> {
+        let (tx, prevouts) = self.build_refund_tx(htlc, signer, fee_rate).await?;
+        let (leaf_script, control_block) = htlc.get_control_block(Leaf::Refund)?;
+        leaf_spend_psbt(tx, prevouts, leaf_script, &control_block, signer)
+    }
+
+    /// Replaces the unconfirmed transaction funding `htlc_addr` with one paying `fee_rate`,
+    /// or at least the minimum a replacement must add. The HTLC output is kept as it is and
+    /// the extra fee comes out of the change, which is dropped once it would be dust.
+    pub async fn bump_funding_tx(
+        &self,
+        htlc_addr: &Address,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<Transaction> {
+        let htlc_script = htlc_addr.script_pubkey();
+        let own_script = self.wallet_script.script_pubkey(&signer.btc_public_key())?;
+
+        let pending = self
+            .pending_tx(htlc_addr, |tx| tx.pays_to(&htlc_script))
+            .await?
+            .ok_or_else(|| anyhow!("no unconfirmed funding transaction for {}", htlc_addr))?;
+        let prevouts = pending.prevouts()?;
+        if prevouts.iter().any(|prevout| prevout.script_pubkey != own_script) {
+            return Err(anyhow!("funding tx {} spends outputs this wallet does not own", pending.txid));
+        }
+
+        let mut tx = self.indexer.get_tx(&pending.txid).await?;
+        let change_index = tx
+            .output
+            .iter()
+            .position(|output| output.script_pubkey == own_script)
+            .ok_or_else(|| anyhow!("funding tx {} has no change to pay a higher fee from", pending.txid))?;
+
+        let scripts: Vec<&Script> = tx.output.iter().map(|output| output.script_pubkey.as_script()).collect();
+        let weight = fee::wallet_spend_weight(self.wallet_script, tx.input.len(), &scripts);
+        let extra_fee = replacement_fee(weight, fee_rate, pending.fee) - pending.fee;
+
+        take_fee_from_change(&mut tx, change_index, extra_fee)?;
+
+        for input in tx.input.iter_mut() {
+            input.witness = Witness::new();
+        }
+        self.sign_wallet_inputs(tx, &prevouts, signer)
+    }
+
+    /// Replaces the unconfirmed transaction redeeming `htlc` with one paying `fee_rate`, or at
+    /// least the minimum a replacement must add, taken out of the single output.
+    pub async fn bump_redeem_tx(
+        &self,
+        htlc: &BitcoinHTLC,
+        witness_stack: Vec<Vec<u8>>,
+        signer: &dyn WalletSigner,
+        fee_rate: u64,
+    ) -> Result<Transaction> {
+        let htlc_addr = htlc.address()?;
+        let htlc_script = htlc_addr.script_pubkey();
+
+        let pending = self
+            .pending_tx(&htlc_addr, |tx| tx.spends_from(&htlc_script))
+            .await?
+            .ok_or_else(|| anyhow!("no unconfirmed redeem transaction for {}", htlc_addr))?;
+        let prevouts = pending.prevouts()?;
+
+        let mut tx = self.indexer.get_tx(&pending.txid).await?;
+        if tx.input.len() != 1 || tx.output.len() != 1 {
+            return Err(anyhow!("tx {} is not a redeem built by this wallet", pending.txid));
+        }
+
+        let output_script = tx.output[0].script_pubkey.clone();
+        let weight = fee::leaf_spend_weight(&witness_stack, &output_script);
+        let fee = replacement_fee(weight, fee_rate, pending.fee);
+        let output_value = leaf_spend_output(prevouts[0].value.to_sat(), fee, &output_script)?;
+        tx.output[0].value = Amount::from_sat(output_value);
+
+        let leaf_hash = TapLeafHash::from_script(
+            Script::from_bytes(&witness_stack[2]),
+            LeafVersion::TapScript,
+        );
+
+        self.sign_and_set_taproot_witness(
+            tx,
+            0,
+            leaf_hash,
+            signer,
+            TapSighashType::All,
+            prevouts,
+            witness_stack
+        )
+    }
+
+    /// First unconfirmed transaction of `address` that `matches`.
+    async fn pending_tx(&self, address: &Address, matches: impl Fn(&IndexedTx) -> bool) -> Result<Option<IndexedTx>> {
+        let txs = self.indexer.get_address_txs(&address.to_string()).await?;
+        Ok(txs.into_iter().find(|tx| !tx.status.confirmed && matches(tx)))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn sign_and_set_taproot_witness(
         &self,
         mut tx: Transaction,
         input_index: usize,
         leaf_hash: TapLeafHash,
-        private_key: bitcoin::PrivateKey,
+        signer: &dyn WalletSigner,
         sighash_type: TapSighashType,
         prevouts: Vec<bitcoin::TxOut>,
         witness_stack: Vec<Vec<u8>>
     ) -> Result<Transaction> {
-        // Create keypair from private key
-        let secp = Secp256k1::new();
-        let keypair = Keypair::from_secret_key(&secp, &private_key.inner);
-
         // Create sighash cache for the transaction
         let mut sighash_cache = SighashCache::new(&tx);
 
@@ -325,7 +707,7 @@ impl HtlcHandler {
         let message = Message::from_digest_slice(tap_sighash.as_ref())?;
 
         // Sign the sighash with Schnorr signature
-        let signature = secp.sign_schnorr_no_aux_rand(&message, &keypair);
+        let signature = signer.sign_btc_schnorr(&message)?;
 
         let mut sig_serialized = signature.as_ref().to_vec();
         if sighash_type != TapSighashType::Default {
@@ -335,11 +717,11 @@ impl HtlcHandler {
         // Create the witness
         let mut witness = Witness::new();
 
-        // Add the signature as the first element
+        // Add the signature in place of the placeholder, followed by the rest of the leaf's stack
         witness.push(sig_serialized);
-        witness.push(&witness_stack[1]);
-        witness.push(&witness_stack[2]);
-        witness.push(&witness_stack[3]);
+        for element in witness_stack.iter().skip(1) {
+            witness.push(element);
+        }
 
         tx.input[input_index].witness = witness;
 
@@ -347,6 +729,77 @@ impl HtlcHandler {
     }
 }
 
+/// Fee of a replacement weighing `weight`: at least `fee_rate`, and per BIP125 the
+/// replaced `fee` plus the minimum relay fee for the replacement's own size.
+fn replacement_fee(weight: Weight, fee_rate: u64, fee: u64) -> u64 {
+    let required = fee + fee::fee_for(weight, MIN_RELAY_FEE_RATE);
+    fee::fee_for(weight, fee_rate).max(required)
+}
+
+/// Takes `extra_fee` out of the change output at `change_index`. The change is dropped
+/// when what is left of it would be dust, so that goes to the fee as well.
+fn take_fee_from_change(tx: &mut Transaction, change_index: usize, extra_fee: u64) -> Result<()> {
+    let change_output = &mut tx.output[change_index];
+    let change = change_output.value.to_sat();
+    match change.checked_sub(extra_fee) {
+        Some(left) if !fee::is_dust(left, &change_output.script_pubkey) => {
+            change_output.value = Amount::from_sat(left);
+        }
+        Some(_) => {
+            tx.output.remove(change_index);
+        }
+        None => {
+            return Err(anyhow!(
+                "change of {} sats in funding tx {} cannot pay {} sats more fee",
+                change, tx.compute_txid(), extra_fee
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Whether an output confirmed at `block_height` can be spent through a CSV of `timelock`
+/// blocks by the block after `tip_height`. CSV counts the confirming block, so the spend can
+/// be mined at `block_height + timelock`.
+fn csv_matured(tip_height: u64, block_height: u64, timelock: i64) -> bool {
+    tip_height + 1 >= block_height + timelock as u64
+}
+
+/// What is left of the HTLC's `value` for the single output of a leaf spend after `fee`.
+fn leaf_spend_output(value: u64, fee: u64, script: &Script) -> Result<u64> {
+    match value.checked_sub(fee) {
+        Some(output) if !fee::is_dust(output, script) => Ok(output),
+        _ => Err(anyhow!("htlc holds {} sats, too little to pay a {} sats fee and leave a spendable output", value, fee)),
+    }
+}
+
+/// PSBT for a script path spend of one of the HTLC's leaves, with one input per HTLC output.
+/// The signer finds the leaf script and control block in `tap_scripts`, and the leaf its key
+/// signs for in `tap_key_origins`.
+fn leaf_spend_psbt(
+    tx: Transaction,
+    prevouts: Vec<TxOut>,
+    leaf_script: ScriptBuf,
+    control_block: &[u8],
+    signer: &dyn WalletSigner,
+) -> Result<Psbt> {
+    let control_block = ControlBlock::decode(control_block)
+        .map_err(|e| anyhow!("invalid control block: {}", e))?;
+    let leaf_hash = TapLeafHash::from_script(&leaf_script, LeafVersion::TapScript);
+    let (x_only, _) = signer.btc_public_key().inner.x_only_public_key();
+
+    let mut psbt = Psbt::from_unsigned_tx(tx)?;
+    for (input, prevout) in psbt.inputs.iter_mut().zip(prevouts) {
+        input.witness_utxo = Some(prevout);
+        input.sighash_type = Some(TapSighashType::All.into());
+        input.tap_internal_key = Some(control_block.internal_key);
+        input.tap_scripts.insert(control_block.clone(), (leaf_script.clone(), LeafVersion::TapScript));
+        input.tap_key_origins.insert(x_only, (vec![leaf_hash], signer.btc_key_origin()));
+    }
+
+    Ok(psbt)
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(dead_code)]
 pub struct UTXO {
@@ -356,6 +809,59 @@ pub struct UTXO {
     pub value: u64,
 }
 
+/// Transaction as listed by Esplora, with the outputs its inputs spend.
+#[derive(Debug, Deserialize, Clone)]
+pub struct IndexedTx {
+    pub txid: String,
+    pub vin: Vec<IndexedTxIn>,
+    pub vout: Vec<IndexedTxOut>,
+    pub fee: u64,
+    pub status: Status,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IndexedTxIn {
+    pub prevout: Option<IndexedTxOut>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IndexedTxOut {
+    /// Hex encoded script pubkey.
+    pub scriptpubkey: String,
+    pub value: u64,
+}
+
+impl IndexedTx {
+    fn pays_to(&self, script: &Script) -> bool {
+        let script = hex::encode(script.as_bytes());
+        self.vout.iter().any(|output| output.scriptpubkey == script)
+    }
+
+    fn spends_from(&self, script: &Script) -> bool {
+        let script = hex::encode(script.as_bytes());
+        self.vin
+            .iter()
+            .any(|input| input.prevout.as_ref().is_some_and(|prevout| prevout.scriptpubkey == script))
+    }
+
+    /// Outputs spent by the inputs, in input order, as needed for signing.
+    fn prevouts(&self) -> Result<Vec<TxOut>> {
+        self.vin
+            .iter()
+            .map(|input| {
+                let prevout = input
+                    .prevout
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("indexer returned tx {} without its prevouts", self.txid))?;
+                Ok(TxOut {
+                    value: Amount::from_sat(prevout.value),
+                    script_pubkey: ScriptBuf::from_hex(&prevout.scriptpubkey)?,
+                })
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(dead_code)]
 pub struct Status {
@@ -421,3 +927,122 @@ pub struct Status {
         
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash;
+
+    use super::*;
+
+    #[test]
+    fn csv_matures_in_the_block_at_height_plus_timelock() {
+        // confirmed at 100 with a 144 block timelock, the refund can be mined at 244
+        assert!(!csv_matured(242, 100, 144));
+        assert!(csv_matured(243, 100, 144));
+        assert!(csv_matured(300, 100, 144));
+    }
+
+    #[test]
+    fn csv_of_one_block_matures_right_after_confirmation() {
+        assert!(csv_matured(100, 100, 1));
+        assert!(!csv_matured(99, 100, 2));
+    }
+
+    #[test]
+    fn replacement_pays_old_fee_plus_incremental_relay_fee() {
+        let weight = Weight::from_vb(141).unwrap();
+        // BIP125 rule 4: 200 sats replaced + 1 sat/vB for the replacement's 141 vB
+        assert_eq!(replacement_fee(weight, 2, 200), 341);
+        // a target rate above that minimum is paid in full
+        assert_eq!(replacement_fee(weight, 5, 200), 705);
+    }
+
+    #[test]
+    fn key_spend_inputs_verify_against_the_wallet_output() {
+        use alloy::{primitives::B256, signers::local::PrivateKeySigner};
+        use bitcoin::{key::{Secp256k1, TapTweak}, secp256k1::{schnorr, SecretKey}};
+        use crate::service::signer::local::LocalWalletSigner;
+
+        let signer = LocalWalletSigner::new(
+            PrivateKeySigner::from_bytes(&B256::repeat_byte(0x22)).unwrap(),
+            SecretKey::from_slice(&[0x11; 32]).unwrap(),
+        );
+        let handler = HtlcHandler::new(bitcoin::Network::Regtest, "http://localhost:30000")
+            .unwrap()
+            .with_wallet_script(WalletScript::P2tr);
+        let own_script = WalletScript::P2tr.script_pubkey(&signer.btc_public_key()).unwrap();
+
+        let mut tx = funding_tx(10_000);
+        let prevouts: Vec<TxOut> = [70_000, 30_000]
+            .into_iter()
+            .map(|value| TxOut { value: Amount::from_sat(value), script_pubkey: own_script.clone() })
+            .collect();
+        for vout in 0..prevouts.len() as u32 {
+            tx.input.push(TxIn {
+                previous_output: OutPoint { txid: Txid::all_zeros(), vout },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            });
+        }
+
+        let signed = handler.sign_wallet_inputs(tx, &prevouts, &signer).unwrap();
+
+        let secp = Secp256k1::verification_only();
+        let (internal_key, _) = signer.btc_public_key().inner.x_only_public_key();
+        let (output_key, _) = internal_key.tap_tweak(&secp, None);
+        let mut cache = SighashCache::new(&signed);
+        for (i, input) in signed.input.iter().enumerate() {
+            // a key path witness is the bare 64 byte signature of the default sighash
+            assert_eq!(input.witness.len(), 1);
+            let signature = schnorr::Signature::from_slice(&input.witness[0]).unwrap();
+            let sighash = cache
+                .taproot_key_spend_signature_hash(i, &Prevouts::All(&prevouts), TapSighashType::Default)
+                .unwrap();
+            assert!(secp.verify_schnorr(&signature, &Message::from(sighash), &output_key.to_inner()).is_ok());
+        }
+    }
+
+    fn funding_tx(change: u64) -> Transaction {
+        let htlc_script = ScriptBuf::new_p2tr_tweaked(
+            bitcoin::key::TweakedPublicKey::dangerous_assume_tweaked(
+                bitcoin::XOnlyPublicKey::from_slice(&[2; 32]).unwrap(),
+            ),
+        );
+        let change_script = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([1; 20]));
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                TxOut { value: Amount::from_sat(50_000), script_pubkey: htlc_script },
+                TxOut { value: Amount::from_sat(change), script_pubkey: change_script },
+            ],
+        }
+    }
+
+    #[test]
+    fn bump_takes_extra_fee_from_change() {
+        let mut tx = funding_tx(10_000);
+        take_fee_from_change(&mut tx, 1, 1_000).unwrap();
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[0].value.to_sat(), 50_000);
+        assert_eq!(tx.output[1].value.to_sat(), 9_000);
+    }
+
+    #[test]
+    fn bump_drops_change_that_would_be_dust() {
+        // P2WPKH dust is below 294 sats
+        let mut tx = funding_tx(1_200);
+        take_fee_from_change(&mut tx, 1, 1_000).unwrap();
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value.to_sat(), 50_000);
+    }
+
+    #[test]
+    fn bump_fails_when_change_cannot_pay() {
+        let mut tx = funding_tx(500);
+        assert!(take_fee_from_change(&mut tx, 1, 1_000).is_err());
+        assert_eq!(tx.output[1].value.to_sat(), 500);
+    }
+}
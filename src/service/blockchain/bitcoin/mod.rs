@@ -1,3 +1,7 @@
 pub mod htlc;
 pub mod scripts;
-pub mod htlc_handler;
\ No newline at end of file
+pub mod htlc_handler;
+pub mod psbt;
+pub mod fee;
+pub mod coin_selection;
+pub mod wallet;
\ No newline at end of file
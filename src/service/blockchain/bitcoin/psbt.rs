@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bitcoin::{psbt::Psbt, taproot::LeafVersion, Script, TapLeafHash, Transaction, Witness};
+
+/// Base64, the encoding wallets and `bitcoin-cli` exchange PSBTs in.
+pub fn encode(psbt: &Psbt) -> String {
+    STANDARD.encode(psbt.serialize())
+}
+
+pub fn decode(psbt: &str) -> Result<Psbt> {
+    let bytes = STANDARD.decode(psbt.trim()).context("PSBT is not valid base64")?;
+    Psbt::deserialize(&bytes).context("Failed to parse PSBT")
+}
+
+/// Builds the P2WPKH or taproot key path witnesses of a signed funding PSBT and extracts the
+/// transaction. Inputs the signer already finalized are kept as they are.
+pub fn finalize_funding(mut psbt: Psbt) -> Result<Transaction> {
+    for (i, input) in psbt.inputs.iter_mut().enumerate() {
+        if input.final_script_witness.is_some() {
+            continue;
+        }
+
+        let witness = match (&input.tap_key_sig, input.partial_sigs.iter().next()) {
+            (Some(signature), _) => Witness::p2tr_key_spend(signature),
+            (None, Some((public_key, signature))) => Witness::p2wpkh(signature, &public_key.inner),
+            (None, None) => return Err(anyhow!("input {} of the PSBT is not signed", i)),
+        };
+
+        input.final_script_witness = Some(witness);
+        input.partial_sigs.clear();
+        input.bip32_derivation.clear();
+        input.tap_key_sig = None;
+        input.tap_internal_key = None;
+        input.tap_key_origins.clear();
+    }
+
+    extract(psbt)
+}
+
+/// Builds the script path witnesses of a signed redeem or refund PSBT. `witness_stack` is the
+/// leaf's witness from `BitcoinHTLC`, whose first element is the signature placeholder and whose
+/// last two are the leaf script and its control block.
+pub fn finalize_leaf_spend(mut psbt: Psbt, witness_stack: &[Vec<u8>]) -> Result<Transaction> {
+    if psbt.inputs.is_empty() {
+        return Err(anyhow!("PSBT has no inputs"));
+    }
+    let [_, .., leaf_script, _] = witness_stack else {
+        return Err(anyhow!("leaf witness has no leaf script and control block"));
+    };
+    // a signer may have signed other leaves as well, only this leaf's signature fits the witness
+    let leaf_hash = TapLeafHash::from_script(Script::from_bytes(leaf_script), LeafVersion::TapScript);
+
+    for (i, input) in psbt.inputs.iter_mut().enumerate() {
+        if input.final_script_witness.is_some() {
+            continue;
+        }
+
+        let signature = input
+            .tap_script_sigs
+            .iter()
+            .find(|((_, signed_leaf), _)| *signed_leaf == leaf_hash)
+            .map(|(_, signature)| signature)
+            .ok_or_else(|| anyhow!("input {} of the PSBT is not signed for leaf {}", i, leaf_hash))?;
+
+        let mut witness = Witness::new();
+        witness.push(signature.to_vec());
+        for element in witness_stack.iter().skip(1) {
+            witness.push(element);
+        }
+
+        input.final_script_witness = Some(witness);
+        input.tap_script_sigs.clear();
+        input.tap_scripts.clear();
+        input.tap_key_origins.clear();
+    }
+
+    extract(psbt)
+}
+
+fn extract(psbt: Psbt) -> Result<Transaction> {
+    psbt.extract_tx()
+        .map_err(|e| anyhow!("Failed to extract the transaction from the PSBT: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        absolute::LockTime,
+        ecdsa,
+        hashes::Hash,
+        key::{Keypair, Secp256k1, TapTweak},
+        secp256k1::{All, Message, SecretKey},
+        sighash::{Prevouts, SighashCache},
+        taproot,
+        transaction::Version,
+        Amount, EcdsaSighashType, OutPoint, PublicKey, ScriptBuf, Sequence, TapSighashType, TxIn, TxOut, Txid,
+    };
+
+    use super::*;
+    use crate::service::blockchain::bitcoin::htlc::{BitcoinHTLC, Leaf};
+    use crate::service::blockchain::bitcoin::wallet::WalletScript;
+
+    fn keypair(secp: &Secp256k1<All>, byte: u8) -> Keypair {
+        Keypair::from_secret_key(secp, &SecretKey::from_slice(&[byte; 32]).unwrap())
+    }
+
+    /// Unsigned PSBT spending one output of `script_pubkey` per value in `values`.
+    fn psbt(script_pubkey: &ScriptBuf, values: &[u64]) -> Psbt {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: (0..values.len() as u32)
+                .map(|vout| TxIn {
+                    previous_output: OutPoint { txid: Txid::all_zeros(), vout },
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(values.iter().sum::<u64>() - 1_000),
+                script_pubkey: script_pubkey.clone(),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (input, value) in psbt.inputs.iter_mut().zip(values) {
+            input.witness_utxo = Some(TxOut { value: Amount::from_sat(*value), script_pubkey: script_pubkey.clone() });
+        }
+        psbt
+    }
+
+    fn prevouts(psbt: &Psbt) -> Vec<TxOut> {
+        psbt.inputs.iter().map(|input| input.witness_utxo.clone().unwrap()).collect()
+    }
+
+    #[test]
+    fn finalize_funding_builds_p2wpkh_witnesses() {
+        let secp = Secp256k1::new();
+        let key = keypair(&secp, 0x11);
+        let public_key = PublicKey::new(key.public_key());
+        let script = WalletScript::P2wpkh.script_pubkey(&public_key).unwrap();
+
+        let mut psbt = psbt(&script, &[60_000, 40_000]);
+        let prevouts = prevouts(&psbt);
+        let mut cache = SighashCache::new(&psbt.unsigned_tx);
+        for (i, input) in psbt.inputs.iter_mut().enumerate() {
+            let sighash = cache
+                .p2wpkh_signature_hash(i, &prevouts[i].script_pubkey, prevouts[i].value, EcdsaSighashType::All)
+                .unwrap();
+            let signature = secp.sign_ecdsa(&Message::from(sighash), &key.secret_key());
+            input.partial_sigs.insert(public_key, ecdsa::Signature { signature, sighash_type: EcdsaSighashType::All });
+        }
+
+        let tx = finalize_funding(psbt).unwrap();
+        for input in &tx.input {
+            // the DER signature with its sighash byte, then the compressed public key
+            assert_eq!(input.witness.len(), 2);
+            assert_eq!(input.witness[0].last(), Some(&(EcdsaSighashType::All as u8)));
+            assert_eq!(input.witness[1], public_key.to_bytes());
+        }
+    }
+
+    #[test]
+    fn finalize_funding_builds_key_path_witnesses() {
+        let secp = Secp256k1::new();
+        let key = keypair(&secp, 0x11);
+        let script = WalletScript::P2tr.script_pubkey(&PublicKey::new(key.public_key())).unwrap();
+        let tweaked = key.tap_tweak(&secp, None).to_inner();
+
+        let mut psbt = psbt(&script, &[60_000, 40_000]);
+        let prevouts = prevouts(&psbt);
+        let mut cache = SighashCache::new(&psbt.unsigned_tx);
+        for (i, input) in psbt.inputs.iter_mut().enumerate() {
+            let sighash = cache
+                .taproot_key_spend_signature_hash(i, &Prevouts::All(&prevouts), TapSighashType::Default)
+                .unwrap();
+            let signature = secp.sign_schnorr_no_aux_rand(&Message::from(sighash), &tweaked);
+            input.tap_key_sig = Some(taproot::Signature { signature, sighash_type: TapSighashType::Default });
+        }
+
+        let tx = finalize_funding(psbt).unwrap();
+        for input in &tx.input {
+            // a bare 64 byte signature, the default sighash has no trailing byte
+            assert_eq!(input.witness.len(), 1);
+            assert_eq!(input.witness[0].len(), 64);
+        }
+    }
+
+    #[test]
+    fn finalize_funding_rejects_unsigned_inputs() {
+        let secp = Secp256k1::new();
+        let public_key = PublicKey::new(keypair(&secp, 0x11).public_key());
+        let script = WalletScript::P2wpkh.script_pubkey(&public_key).unwrap();
+
+        let err = finalize_funding(psbt(&script, &[60_000])).unwrap_err();
+        assert!(err.to_string().contains("input 0 of the PSBT is not signed"), "{}", err);
+    }
+
+    fn htlc(secp: &Secp256k1<All>) -> BitcoinHTLC {
+        let x_only = |byte| hex::encode(keypair(secp, byte).x_only_public_key().0.serialize());
+        BitcoinHTLC::new(vec![1; 32], x_only(0x11), x_only(0x22), 144, bitcoin::Network::Regtest).unwrap()
+    }
+
+    #[test]
+    fn finalize_leaf_spend_uses_the_signature_for_the_spent_leaf() {
+        let secp = Secp256k1::new();
+        let key = keypair(&secp, 0x11);
+        let (x_only, _) = key.x_only_public_key();
+        let htlc = htlc(&secp);
+        let witness_stack = htlc.refund().unwrap();
+        let refund_leaf = TapLeafHash::from_script(Script::from_bytes(&witness_stack[1]), LeafVersion::TapScript);
+        let (redeem_script, _) = htlc.get_control_block(Leaf::Redeem).unwrap();
+        let redeem_leaf = TapLeafHash::from_script(&redeem_script, LeafVersion::TapScript);
+
+        let mut psbt = psbt(&htlc.address().unwrap().script_pubkey(), &[60_000, 40_000]);
+        let prevouts = prevouts(&psbt);
+        let mut cache = SighashCache::new(&psbt.unsigned_tx);
+        let mut signatures = Vec::new();
+        for (i, input) in psbt.inputs.iter_mut().enumerate() {
+            for leaf_hash in [refund_leaf, redeem_leaf] {
+                let sighash = cache
+                    .taproot_script_spend_signature_hash(i, &Prevouts::All(&prevouts), leaf_hash, TapSighashType::All)
+                    .unwrap();
+                let signature = taproot::Signature {
+                    signature: secp.sign_schnorr_no_aux_rand(&Message::from(sighash), &key),
+                    sighash_type: TapSighashType::All,
+                };
+                input.tap_script_sigs.insert((x_only, leaf_hash), signature);
+                if leaf_hash == refund_leaf {
+                    signatures.push(signature);
+                }
+            }
+        }
+
+        let tx = finalize_leaf_spend(psbt, &witness_stack).unwrap();
+        for (input, signature) in tx.input.iter().zip(signatures) {
+            // the refund leaf's signature, then the leaf script and its control block
+            let witness: Vec<&[u8]> = input.witness.iter().collect();
+            assert_eq!(witness, [signature.to_vec().as_slice(), &witness_stack[1], &witness_stack[2]]);
+        }
+    }
+
+    #[test]
+    fn finalize_leaf_spend_rejects_unsigned_inputs() {
+        let secp = Secp256k1::new();
+        let htlc = htlc(&secp);
+        let witness_stack = htlc.refund().unwrap();
+
+        let psbt = psbt(&htlc.address().unwrap().script_pubkey(), &[60_000]);
+        let err = finalize_leaf_spend(psbt, &witness_stack).unwrap_err();
+        assert!(err.to_string().contains("input 0 of the PSBT is not signed"), "{}", err);
+    }
+
+    #[test]
+    fn finalize_leaf_spend_rejects_a_short_witness_stack() {
+        let secp = Secp256k1::new();
+        let psbt = psbt(&htlc(&secp).address().unwrap().script_pubkey(), &[60_000]);
+        assert!(finalize_leaf_spend(psbt, &[vec![0; 6]]).is_err());
+    }
+}
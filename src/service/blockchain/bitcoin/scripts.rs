@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use bitcoin::{ScriptBuf, Script, opcodes};
 
-pub fn redeem_leaf(secret_hash_bytes: &Vec<u8>, redeemer_pubkey: &str) -> Result<ScriptBuf> {
+pub fn redeem_leaf(secret_hash_bytes: &[u8], redeemer_pubkey: &str) -> Result<ScriptBuf> {
     if secret_hash_bytes.len() != 32 {
         return Err(anyhow!(
             "Secret hash must be 32 bytes (64 hex chars), got {} bytes",
@@ -10,7 +10,7 @@ pub fn redeem_leaf(secret_hash_bytes: &Vec<u8>, redeemer_pubkey: &str) -> Result
     }
 
     let mut secret_hash_array = [0u8; 32];
-    secret_hash_array.copy_from_slice(&secret_hash_bytes);
+    secret_hash_array.copy_from_slice(secret_hash_bytes);
 
     let bytes = hex::decode(redeemer_pubkey)?;
     let mut redeem_pub_array = [0u8; 32];
@@ -20,7 +20,7 @@ pub fn redeem_leaf(secret_hash_bytes: &Vec<u8>, redeemer_pubkey: &str) -> Result
         .push_opcode(opcodes::all::OP_SHA256)
         .push_slice(secret_hash_array)
         .push_opcode(opcodes::all::OP_EQUALVERIFY)
-        .push_slice(&redeem_pub_array)
+        .push_slice(redeem_pub_array)
         .push_opcode(opcodes::all::OP_CHECKSIG)
         .into_script();
 
@@ -28,7 +28,7 @@ pub fn redeem_leaf(secret_hash_bytes: &Vec<u8>, redeemer_pubkey: &str) -> Result
 }
 
 pub fn refund_leaf(timelock: i64, initiator_pubkey: &str) -> Result<ScriptBuf> {
-    let bytes = hex::decode(&initiator_pubkey)?;
+    let bytes = hex::decode(initiator_pubkey)?;
     let mut init_pub_array = [0u8; 32];
     init_pub_array.copy_from_slice(&bytes[0..32]);
 
@@ -44,18 +44,18 @@ pub fn refund_leaf(timelock: i64, initiator_pubkey: &str) -> Result<ScriptBuf> {
 }
 
 pub fn instant_refund_leaf(initiator_pubkey: &str, redeemer_pubkey: &str) -> Result<ScriptBuf> {
-    let bytes = hex::decode(&initiator_pubkey)?;
+    let bytes = hex::decode(initiator_pubkey)?;
     let mut init_pub_array = [0u8; 32];
     init_pub_array.copy_from_slice(&bytes[0..32]);
 
-    let bytes = hex::decode(&redeemer_pubkey)?;
+    let bytes = hex::decode(redeemer_pubkey)?;
     let mut redeem_pub_array = [0u8; 32];
     redeem_pub_array.copy_from_slice(&bytes[0..32]);
 
     let script = Script::builder()
-        .push_slice(&init_pub_array)
+        .push_slice(init_pub_array)
         .push_opcode(opcodes::all::OP_CHECKSIG)
-        .push_slice(&redeem_pub_array)
+        .push_slice(redeem_pub_array)
         .push_opcode(opcodes::all::OP_CHECKSIGADD)
         .push_opcode(opcodes::all::OP_PUSHNUM_2)
         .push_opcode(opcodes::all::OP_NUMEQUAL)
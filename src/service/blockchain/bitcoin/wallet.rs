@@ -0,0 +1,39 @@
+use anyhow::Result;
+use bitcoin::{
+    key::Secp256k1, transaction::InputWeightPrediction, Address, CompressedPublicKey, ScriptBuf,
+};
+
+/// Script type of the wallet's own outputs: the UTXOs funding an HTLC, change and refunds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WalletScript {
+    /// BIP84 native segwit, spent with an ECDSA signature.
+    #[default]
+    P2wpkh,
+    /// BIP86 taproot, spent through the key path of an output without a script tree.
+    P2tr,
+}
+
+impl WalletScript {
+    pub fn address(&self, public_key: &bitcoin::PublicKey, network: bitcoin::Network) -> Result<Address> {
+        Ok(match self {
+            WalletScript::P2wpkh => Address::p2wpkh(&CompressedPublicKey::try_from(*public_key)?, network),
+            WalletScript::P2tr => {
+                let (internal_key, _) = public_key.inner.x_only_public_key();
+                Address::p2tr(&Secp256k1::verification_only(), internal_key, None, network)
+            }
+        })
+    }
+
+    pub fn script_pubkey(&self, public_key: &bitcoin::PublicKey) -> Result<ScriptBuf> {
+        // the network only changes how the address is written, not its script
+        Ok(self.address(public_key, bitcoin::Network::Bitcoin)?.script_pubkey())
+    }
+
+    /// Largest input spending one of the wallet's outputs, as the fee is paid before signing.
+    pub fn input_weight(&self) -> InputWeightPrediction {
+        match self {
+            WalletScript::P2wpkh => InputWeightPrediction::P2WPKH_MAX,
+            WalletScript::P2tr => InputWeightPrediction::P2TR_KEY_DEFAULT_SIGHASH,
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use std::{convert::TryFrom, str::FromStr};
 use alloy::{
-    hex::FromHex, network::EthereumWallet, primitives::{Address, FixedBytes, Uint, U256}, providers::ProviderBuilder, signers::{local::PrivateKeySigner, Signature, Signer}, sol_types::eip712_domain
+    hex::FromHex, network::EthereumWallet, primitives::{Address, Bytes, FixedBytes, TxHash, Uint, U256}, providers::{Provider, ProviderBuilder}, signers::Signature, sol_types::eip712_domain
 };
+use anyhow::{anyhow, Result};
 use reqwest::Url;
 
 use crate::service::garden::types::SingleSwap;
+use crate::service::signer::{EvmTxSigner, SharedSigner};
 
 
 alloy::sol!(
@@ -58,13 +60,14 @@ impl TryFrom<&SingleSwap> for Initiate {
 pub async fn init_and_get_sig(
     init_data: Initiate,
     provider_url: &str,
-    signer: PrivateKeySigner,
+    signer: SharedSigner,
     token_address: &str,
-) -> Signature {
+) -> Result<Signature> {
     
-    let eth_wallet = EthereumWallet::new(signer.clone());
+    let eth_wallet = EthereumWallet::new(EvmTxSigner(signer.clone()));
     
-    let provider_url = Url::from_str(&provider_url).unwrap();
+    let provider_url = Url::from_str(provider_url)
+        .map_err(|e| anyhow!("invalid provider url: {}", e))?;
     
     let provider = ProviderBuilder::new()
         .with_recommended_fillers()
@@ -74,10 +77,11 @@ pub async fn init_and_get_sig(
     
     
     let htlc_contract = GardenHTLC::new(
-        alloy::primitives::Address::from_hex(token_address).unwrap(),
+        alloy::primitives::Address::from_hex(token_address)?,
         provider.clone(),
     );
-    let d = htlc_contract.eip712Domain().call().await.unwrap();
+    let d = htlc_contract.eip712Domain().call().await
+        .map_err(|e| anyhow!("Failed to get eip712 domain: {}", e))?;
 
     let domain = eip712_domain! {
         name: d.name,
@@ -90,7 +94,7 @@ pub async fn init_and_get_sig(
         .token()
         .call()
         .await
-        .expect("Failed to get token address")
+        .map_err(|e| anyhow!("Failed to get token address: {}", e))?
         ._0;
 
     let _erc20 = ERC20::new(token_address, provider.clone());
@@ -99,10 +103,184 @@ pub async fn init_and_get_sig(
         .approve(*htlc_contract.address(), U256::MAX)
         .send()
         .await
-        .unwrap()
+        .map_err(|e| anyhow!("Failed to send approval: {}", e))?
         .watch()
+        .await?;
+    let sig = signer.sign_typed_data(&init_data, &domain)?;
+    Ok(sig)
+}
+
+/// On-chain state of an order in the `GardenHTLC` contract's `orders` mapping.
+#[derive(Debug, Clone)]
+pub struct HtlcOrder {
+    pub is_fulfilled: bool,
+    pub initiator: Address,
+    pub redeemer: Address,
+    pub initiated_at: U256,
+    pub timelock: U256,
+    pub amount: U256,
+}
+
+impl HtlcOrder {
+    pub fn exists(&self) -> bool {
+        self.initiator != Address::ZERO
+    }
+
+    /// Blocks left before `refund` is accepted; the contract requires `initiatedAt + timelock < block.number`.
+    pub fn blocks_until_refund(&self, current_block: u64) -> u64 {
+        let expiry = self.initiated_at.saturating_add(self.timelock);
+        let current = U256::from(current_block);
+        // the refund lands in the next block at the earliest
+        if current >= expiry {
+            0
+        } else {
+            (expiry - current).saturating_to::<u64>()
+        }
+    }
+}
+
+pub async fn get_htlc_order(
+    order_id: &str,
+    provider_url: &str,
+    htlc_address: &str,
+) -> Result<(HtlcOrder, u64)> {
+    let provider_url = Url::from_str(provider_url)
+        .map_err(|e| anyhow!("invalid provider url: {}", e))?;
+    let provider = ProviderBuilder::new().on_http(provider_url);
+
+    let htlc_contract = GardenHTLC::new(Address::from_hex(htlc_address)?, provider.clone());
+    let order_id = FixedBytes::<32>::from_hex(order_id)
+        .map_err(|e| anyhow!("Failed to parse order id: {}", e))?;
+
+    let order = htlc_contract
+        .orders(order_id)
+        .call()
+        .await
+        .map_err(|e| anyhow!("Failed to read htlc order: {}", e))?;
+    let current_block = provider.get_block_number().await?;
+
+    Ok((
+        HtlcOrder {
+            is_fulfilled: order.isFulfilled,
+            initiator: order.initiator,
+            redeemer: order.redeemer,
+            initiated_at: order.initiatedAt,
+            timelock: order.timelock,
+            amount: order.amount,
+        },
+        current_block,
+    ))
+}
+
+/// Refunds an initiated order once its timelock has passed.
+pub async fn refund(
+    order_id: &str,
+    provider_url: &str,
+    signer: SharedSigner,
+    htlc_address: &str,
+) -> Result<TxHash> {
+    let (order, current_block) = get_htlc_order(order_id, provider_url, htlc_address).await?;
+
+    if !order.exists() {
+        return Err(anyhow!("order {} is not initiated on chain", order_id));
+    }
+    if order.is_fulfilled {
+        return Err(anyhow!("order {} is already redeemed or refunded", order_id));
+    }
+
+    let remaining = order.blocks_until_refund(current_block);
+    if remaining > 0 {
+        return Err(anyhow!("timelock has not expired yet, {} blocks remaining", remaining));
+    }
+
+    let eth_wallet = EthereumWallet::new(EvmTxSigner(signer));
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(eth_wallet)
+        .on_http(Url::from_str(provider_url)?);
+
+    let htlc_contract = GardenHTLC::new(Address::from_hex(htlc_address)?, provider);
+
+    let tx_hash = htlc_contract
+        .refund(FixedBytes::<32>::from_hex(order_id)?)
+        .send()
         .await
-        .unwrap();
-    let sig = signer.sign_typed_data(&init_data, &domain).await.unwrap();
-    sig
-}
\ No newline at end of file
+        .map_err(|e| anyhow!("Failed to send refund: {}", e))?
+        .watch()
+        .await?;
+
+    Ok(tx_hash)
+}
+
+/// Refunds an initiated order before its timelock using the redeemer's signature over the order id.
+pub async fn instant_refund(
+    order_id: &str,
+    provider_url: &str,
+    signer: SharedSigner,
+    htlc_address: &str,
+    counterparty_signature: &str,
+) -> Result<TxHash> {
+    let (order, _) = get_htlc_order(order_id, provider_url, htlc_address).await?;
+
+    if !order.exists() {
+        return Err(anyhow!("order {} is not initiated on chain", order_id));
+    }
+    if order.is_fulfilled {
+        return Err(anyhow!("order {} is already redeemed or refunded", order_id));
+    }
+
+    let signature = Bytes::from_hex(counterparty_signature)
+        .map_err(|e| anyhow!("Failed to parse counterparty signature: {}", e))?;
+
+    let eth_wallet = EthereumWallet::new(EvmTxSigner(signer));
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(eth_wallet)
+        .on_http(Url::from_str(provider_url)?);
+
+    let htlc_contract = GardenHTLC::new(Address::from_hex(htlc_address)?, provider);
+
+    let tx_hash = htlc_contract
+        .instantRefund(FixedBytes::<32>::from_hex(order_id)?, signature)
+        .send()
+        .await
+        .map_err(|e| anyhow!("Failed to send instant refund: {}", e))?
+        .watch()
+        .await?;
+
+    Ok(tx_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn htlc_order(initiator: Address) -> HtlcOrder {
+        HtlcOrder {
+            is_fulfilled: false,
+            initiator,
+            redeemer: Address::repeat_byte(0x22),
+            initiated_at: U256::from(1_000),
+            timelock: U256::from(7_200),
+            amount: U256::from(50_000),
+        }
+    }
+
+    #[test]
+    fn refund_is_accepted_once_the_next_block_is_past_expiry() {
+        let order = htlc_order(Address::repeat_byte(0x11));
+        let expiry = 1_000 + 7_200;
+        // a refund sent now is mined at expiry, which the strict `<` still rejects
+        assert_eq!(order.blocks_until_refund(expiry - 1), 1);
+        // mined at expiry + 1, after the timelock
+        assert_eq!(order.blocks_until_refund(expiry), 0);
+        assert_eq!(order.blocks_until_refund(expiry + 1), 0);
+        assert_eq!(order.blocks_until_refund(1_000), 7_200);
+    }
+
+    #[test]
+    fn order_exists_only_with_an_initiator() {
+        assert!(htlc_order(Address::repeat_byte(0x11)).exists());
+        assert!(!htlc_order(Address::ZERO).exists());
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt::{self, Display};
+
+use reqwest::{blocking::Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Errors returned by the quote server, authenticator, relayer and orderbook clients.
+#[derive(Debug)]
+pub enum GardenError {
+    /// The request never got a response, e.g. connection refused or timed out.
+    Transport(reqwest::Error),
+    /// The server answered with a non-success status and no API error payload.
+    Status { status: u16, body: String },
+    /// The JWT was rejected, the caller should authenticate again.
+    AuthExpired,
+    /// The response could not be parsed into the expected shape.
+    MalformedResponse(String),
+    /// The API returned an error payload.
+    Api(String),
+    /// The requested strategy is not offered by the quote server.
+    UnknownStrategy(String),
+    /// The orderbook has no matched order with this id.
+    OrderNotFound(String),
+}
+
+impl GardenError {
+    /// Whether repeating the same request may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            GardenError::Transport(_) => true,
+            GardenError::Status { status, .. } => *status >= 500 || *status == 429,
+            _ => false,
+        }
+    }
+}
+
+impl Display for GardenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GardenError::Transport(e) => write!(f, "request failed: {}", e),
+            GardenError::Status { status, body } => write!(f, "server returned {}: {}", status, body),
+            GardenError::AuthExpired => write!(f, "authentication expired"),
+            GardenError::MalformedResponse(msg) => write!(f, "malformed response: {}", msg),
+            GardenError::Api(msg) => write!(f, "api error: {}", msg),
+            GardenError::UnknownStrategy(id) => write!(f, "unknown strategy {}", id),
+            GardenError::OrderNotFound(id) => write!(f, "order {} was not found or is not matched yet", id),
+        }
+    }
+}
+
+impl std::error::Error for GardenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GardenError::Transport(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for GardenError {
+    fn from(e: reqwest::Error) -> Self {
+        GardenError::Transport(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, GardenError>;
+
+/// Unwraps the `{ "status", "result", "error" }` envelope shared by the Garden APIs.
+pub(crate) fn parse_response(resp: Response) -> Result<Value> {
+    let status = resp.status();
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(GardenError::AuthExpired);
+    }
+
+    let body = resp.text()?;
+    let response: Value = match serde_json::from_str(&body) {
+        Ok(response) => response,
+        Err(e) if status.is_success() => {
+            return Err(GardenError::MalformedResponse(format!("{}: {}", e, body)))
+        }
+        Err(_) => {
+            return Err(GardenError::Status {
+                status: status.as_u16(),
+                body,
+            })
+        }
+    };
+
+    if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+        let message = error
+            .as_str()
+            .map(|msg| msg.to_string())
+            .unwrap_or_else(|| error.to_string());
+        return Err(GardenError::Api(message));
+    }
+
+    if !status.is_success() {
+        return Err(GardenError::Status {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| GardenError::MalformedResponse(format!("missing result field in response {}", response)))
+}
+
+pub(crate) fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| GardenError::MalformedResponse(format!("{} in {}", e, value)))
+}
+
+/// Results that are plain strings such as tx hashes or order ids, without the JSON quotes.
+pub(crate) fn result_string(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> Response {
+        http::Response::builder()
+            .status(status)
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn parse_response_unwraps_the_result() {
+        let result = parse_response(response(200, r#"{"status":"Ok","result":"0xabc"}"#)).unwrap();
+        assert_eq!(result_string(result), "0xabc");
+    }
+
+    #[test]
+    fn parse_response_maps_401_to_auth_expired() {
+        let err = parse_response(response(401, r#"{"status":"Error","error":"invalid token"}"#)).unwrap_err();
+        assert!(matches!(err, GardenError::AuthExpired), "{:?}", err);
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn parse_response_keeps_the_status_of_non_json_errors() {
+        for status in [429, 500, 502, 503] {
+            let err = parse_response(response(status, "upstream unavailable")).unwrap_err();
+            assert!(
+                matches!(&err, GardenError::Status { status: s, body } if *s == status && body == "upstream unavailable"),
+                "{:?}",
+                err
+            );
+            assert!(err.is_transient(), "{} should be retried", status);
+        }
+
+        let err = parse_response(response(404, "not found")).unwrap_err();
+        assert!(matches!(err, GardenError::Status { status: 404, .. }), "{:?}", err);
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn parse_response_reads_error_payloads() {
+        // the API reports errors in the envelope, with either status
+        for status in [200, 400, 500] {
+            let err = parse_response(response(status, r#"{"status":"Error","error":"order not found"}"#)).unwrap_err();
+            assert!(matches!(&err, GardenError::Api(message) if message == "order not found"), "{:?}", err);
+            assert!(!err.is_transient());
+        }
+
+        let err = parse_response(response(200, r#"{"status":"Error","error":{"code":7}}"#)).unwrap_err();
+        assert!(matches!(&err, GardenError::Api(message) if message == r#"{"code":7}"#), "{:?}", err);
+    }
+
+    #[test]
+    fn parse_response_rejects_malformed_json() {
+        let cases = ["not json", r#"{"status":"Ok""#, r#"{"status":"Ok"}"#, r#"{"status":"Ok","error":null}"#];
+        for body in cases {
+            let err = parse_response(response(200, body)).unwrap_err();
+            assert!(matches!(err, GardenError::MalformedResponse(_)), "{}: {:?}", body, err);
+            assert!(!err.is_transient());
+        }
+    }
+
+    #[test]
+    fn only_transport_errors_and_server_overload_are_transient() {
+        let not_transient = [
+            GardenError::AuthExpired,
+            GardenError::Api("bad request".to_string()),
+            GardenError::MalformedResponse("eof".to_string()),
+            GardenError::UnknownStrategy("btcarb".to_string()),
+            GardenError::OrderNotFound("1".to_string()),
+            GardenError::Status { status: 400, body: String::new() },
+        ];
+        for err in not_transient {
+            assert!(!err.is_transient(), "{:?}", err);
+        }
+        assert!(GardenError::Status { status: 504, body: String::new() }.is_transient());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod error;
 pub mod orderbook;
 pub mod quote;
+pub mod siwe;
 pub mod types;
\ No newline at end of file
@@ -1,164 +1,230 @@
-use std::{thread::sleep, time::Duration};
+use std::{sync::{Arc, Mutex}, thread::sleep, time::Duration};
 
-use alloy::{hex::ToHexExt, signers::{local::PrivateKeySigner, SignerSync}};
-use anyhow::{anyhow, Result};
+use alloy::hex::ToHexExt;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::Utc;
+use reqwest::blocking::RequestBuilder;
 use serde_json::Value;
 
 
-use super::types::{InitiateRequest, MatchedOrder, Order};
+use super::error::{from_value, parse_response, result_string, GardenError, Result};
+use super::siwe::{SiweConfig, SiweMessage};
+use super::types::{InitiateRequest, MatchedOrder, Order, PaginatedData};
+use crate::service::signer::SharedSigner;
 
+/// How often an order is refetched while waiting on the solver.
+const MATCH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A token is refreshed this many seconds before its `exp` claim.
+const JWT_REFRESH_MARGIN: i64 = 60;
+
+struct Session {
+    jwt: String,
+    /// Unix timestamp from the JWT's `exp` claim, `None` if the token has none.
+    expires_at: Option<i64>,
+}
+
+impl Session {
+    fn new(jwt: String) -> Self {
+        let expires_at = jwt_expiry(&jwt);
+        Session { jwt, expires_at }
+    }
+
+    fn is_expiring(&self) -> bool {
+        self.is_expiring_at(Utc::now().timestamp())
+    }
+
+    fn is_expiring_at(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|exp| now + JWT_REFRESH_MARGIN >= exp)
+    }
+}
+
+/// Clones share the session, so a refresh done by one background task is seen by all.
 #[derive(Clone)]
 pub struct Orderbook {
     client: reqwest::blocking::Client,
     relayer_url: String,
+    auth_url: String,
     orderbook_url: String,
-    jwt: String,
+    signer: SharedSigner,
+    siwe: SiweConfig,
+    session: Arc<Mutex<Session>>,
 }
 
 impl Orderbook {
-    pub fn new(client: reqwest::blocking::Client, relayer_url: &str, auth_url:&str, orderbook_url: &str, signer: &PrivateKeySigner) -> Self {
-        let jwt = authenticate(signer, &auth_url, client.clone()).unwrap();
-        Self { client , relayer_url: relayer_url.to_string(), orderbook_url: orderbook_url.to_string(), jwt }
+    pub fn new(client: reqwest::blocking::Client, relayer_url: &str, auth_url:&str, orderbook_url: &str, signer: SharedSigner, siwe: SiweConfig) -> Result<Self> {
+        let jwt = authenticate(&signer, auth_url, &siwe, client.clone())?;
+        Ok(Self {
+            client,
+            relayer_url: relayer_url.to_string(),
+            auth_url: auth_url.to_string(),
+            orderbook_url: orderbook_url.to_string(),
+            signer,
+            siwe,
+            session: Arc::new(Mutex::new(Session::new(jwt))),
+        })
+    }
+
+    /// Current JWT, signing in again first if it is about to expire.
+    fn token(&self) -> Result<String> {
+        let mut session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        if session.is_expiring() {
+            *session = Session::new(authenticate(&self.signer, &self.auth_url, &self.siwe, self.client.clone())?);
+        }
+        Ok(session.jwt.clone())
+    }
+
+    /// Signs in again after `stale` was rejected, unless another request already replaced it.
+    fn refresh(&self, stale: &str) -> Result<String> {
+        let mut session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        if session.jwt == stale {
+            *session = Session::new(authenticate(&self.signer, &self.auth_url, &self.siwe, self.client.clone())?);
+        }
+        Ok(session.jwt.clone())
+    }
+
+    /// Sends an authenticated request built by `request`, retrying once with a fresh token on 401.
+    fn send_authed<F>(&self, request: F) -> Result<Value>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let jwt = self.token()?;
+        match parse_response(request(&jwt).send()?) {
+            Err(GardenError::AuthExpired) => {
+                let jwt = self.refresh(&jwt)?;
+                parse_response(request(&jwt).send()?)
+            }
+            result => result,
+        }
     }
 
     pub fn create_order(&self, order: Order) -> Result<String> {
         let url = format!("{}/create-order", self.relayer_url);
-        let resp = self.client
-            .post(url)
-            .bearer_auth(&self.jwt)
-            .json(&order)
-            .send()
-            .map_err(|e| anyhow!("failed to send create order request: {}", e))?;
-        
-        if resp.status() == 401 {
-            return Err(anyhow!("401"));
-        }
-        
-        let result = resp.json::<Value>()
-            .map_err(|e| anyhow!("failed to parse response as JSON: {}", e))?;
-        
-        let create_id = result.get("result")
-            .ok_or_else(|| anyhow!("missing result field in response: {}", serde_json::to_string_pretty(&result).unwrap()))?
-            .clone()
-            .to_string();
-        Ok(create_id.trim_matches('"').to_string())
+        let create_id = self.send_authed(|jwt| {
+            self.client
+                .post(&url)
+                .bearer_auth(jwt)
+                .json(&order)
+        })?;
+
+        Ok(result_string(create_id))
     }
     pub fn initiate(&self, init_req: InitiateRequest) -> Result<String> {
         let url = format!("{}/initiate", self.relayer_url);
-        let resp = self.client.post(url)
-            .bearer_auth(&self.jwt)
-            .json(&init_req)
-            .send()?;
-        let result = resp.json::<Value>()?;
-        Ok(result["result"].to_string())
+        let result = self.send_authed(|jwt| {
+            self.client.post(&url)
+                .bearer_auth(jwt)
+                .json(&init_req)
+        })?;
+        Ok(result_string(result))
     }
     pub fn wait_for_destination_init(&self, order_id: &str) -> Result<String> {
         let start_time = std::time::Instant::now();
         let timeout = std::time::Duration::from_secs(60);
-        
+
         loop {
             if start_time.elapsed() > timeout {
-                return Err(anyhow!("Timeout waiting for destination init"));
+                return Err(GardenError::Api("Timeout waiting for destination init".to_string()));
             }
-            
-            let matched_order = self.get_matched_order(order_id)
-                .map_err(|e| anyhow!("Failed to get matched order: {}", e))?;
-                
+
+            let matched_order = self.get_matched_order(order_id)?;
+
             if let Some(init_tx_hash) = matched_order.destination_swap.initiate_tx_hash {
                 if !init_tx_hash.is_empty(){
                     return Ok(init_tx_hash);
                 }
             }
-            sleep(Duration::from_secs(5));
+            sleep(MATCH_POLL_INTERVAL);
         }
     }
-    
+
     pub fn redeem(&self, order_id: &str, secret: &str) -> Result<String> {
         let url = format!("{}/redeem", self.relayer_url);
-        let resp = self.client.post(url).bearer_auth(&self.jwt)
-            .json(&serde_json::json!({
-                "order_id": order_id,
-                "secret": secret,
-                "perform_on": "Destination"
-            }))
-            .send()?;
-        let result = resp.json::<Value>()?;
-        Ok(result["result"].to_string())
+        let body = serde_json::json!({
+            "order_id": order_id,
+            "secret": secret,
+            "perform_on": "Destination"
+        });
+        let result = self.send_authed(|jwt| self.client.post(&url).bearer_auth(jwt).json(&body))?;
+        Ok(result_string(result))
     }
 
     pub fn btc_redeem(&self, order_id: &str, tx_hex: &str) -> Result<String> {
         let url = format!("{}/bitcoin/redeem", self.relayer_url);
-        let resp = self.client.post(url).bearer_auth(&self.jwt)
-            .json(&serde_json::json!({
-                "order_id": order_id,
-                "redeem_tx_bytes": tx_hex
-            }))
+        let body = serde_json::json!({
+            "order_id": order_id,
+            "redeem_tx_bytes": tx_hex
+        });
+        let result = self.send_authed(|jwt| self.client.post(&url).bearer_auth(jwt).json(&body))?;
+        Ok(result_string(result))
+    }
+
+    /// Lists the matched orders of `address`, newest first. Pages start at 1.
+    pub fn get_user_orders(&self, address: &str, page: u64, per_page: u64) -> Result<PaginatedData<MatchedOrder>> {
+        let url = format!("{}/user/{}/matched?page={}&per_page={}", self.orderbook_url, address, page, per_page);
+        let resp = self.client
+            .get(&url)
             .send()?;
-        let result = resp.json::<Value>()?;
-        Ok(result["result"].to_string())
+
+        from_value(parse_response(resp)?)
     }
-    
+
+    /// Fetches the matched order once, failing with `OrderNotFound` if the id is unknown or
+    /// not matched yet.
     pub fn get_matched_order(&self, order_id: &str) -> Result<MatchedOrder> {
+        self.find_matched_order(order_id)?
+            .ok_or_else(|| GardenError::OrderNotFound(order_id.to_string()))
+    }
+
+    /// Fetches a just created order, waiting up to `timeout` for a solver to match it.
+    pub fn wait_for_matched_order(&self, order_id: &str, timeout: Duration) -> Result<MatchedOrder> {
+        let start_time = std::time::Instant::now();
+        loop {
+            if let Some(matched_order) = self.find_matched_order(order_id)? {
+                return Ok(matched_order);
+            }
+            if start_time.elapsed() > timeout {
+                return Err(GardenError::OrderNotFound(order_id.to_string()));
+            }
+
+            sleep(MATCH_POLL_INTERVAL);
+        }
+    }
+
+    /// Fetches the matched order once, `None` if the id is unknown or not matched yet.
+    pub fn find_matched_order(&self, order_id: &str) -> Result<Option<MatchedOrder>> {
         let url = format!("{}/id/{}/matched", self.orderbook_url, order_id);
         let resp = self.client
-            .get(url)
+            .get(&url)
             .send()?;
 
-        let response = resp.json::<Value>()
-            .map_err(|e| anyhow!("error getting matched order {}", e))?;
-
-        let result = response.get("result")
-            .ok_or_else(|| anyhow!("missing result field in response"))?;
-        
-        if result.is_null(){
-            sleep(Duration::from_secs(5));
-            
-            return self.get_matched_order(&order_id);
+        let result = parse_response(resp)?;
+        if result.is_null() {
+            return Ok(None);
         }
-        
-        let order: MatchedOrder = serde_json::from_value(result.clone())
-            .map_err(|e| anyhow!("failed to parse order: {} and result is {} ", e, response))?;
-
-        Ok(order)
+        from_value(result).map(Some)
     }
 }
 
-fn authenticate(signer: &PrivateKeySigner, url: &str, client: reqwest::blocking::Client) -> Result<String, Box<dyn std::error::Error>> {
-    let res = client.post(format!("{}/siwe/challenges", url)).send().expect("error getting nonce");
-
-    let body = res.text()?;
-    let response: serde_json::Value = serde_json::from_str(&body)?;
-    
-    let nonce = response["result"].as_str().ok_or("Failed to get nonce from server")?; // with context
-
-    
-    let current_time = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-    let domain = "localhost:4361"; // Your app's domain
-    let uri = "http://localhost:4361"; // Your app's URI
-    let chain_id = "11155111"; // Sepolia testnet
-    let statement = "Garden.fi"; // Your app's statement
-
-    let msg = format!(
-        "{} wants you to sign in with your Ethereum account:\n\
-        {}\n\n\
-        {}\n\n\
-        URI: {}\n\
-        Version: 1\n\
-        Chain ID: {}\n\
-        Nonce: {}\n\
-        Issued At: {}",
-        domain,
-        signer.address().to_string(),
-        statement,
-        uri,
-        chain_id,
-        nonce,
-        current_time
-    );
-    
+/// Reads the `exp` claim of a JWT without verifying it, the server does that.
+fn jwt_expiry(jwt: &str) -> Option<i64> {
+    let payload = jwt.split('.').nth(1)?;
+    let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    claims.get("exp")?.as_i64()
+}
+
+fn authenticate(signer: &SharedSigner, url: &str, siwe: &SiweConfig, client: reqwest::blocking::Client) -> Result<String> {
+    let res = client.post(format!("{}/siwe/challenges", url)).send()?;
+
+    let nonce = parse_response(res)?;
+    let nonce = nonce.as_str()
+        .ok_or_else(|| GardenError::MalformedResponse("Failed to get nonce from server".to_string()))?;
+
+    let msg = SiweMessage::new(siwe, signer.address(), nonce).to_string();
+
     // 5. Sign the message with your wallet
-    let sig = signer.sign_message_sync(msg.as_bytes())?;
+    let sig = signer.sign_message(msg.as_bytes())
+        .map_err(|e| GardenError::Api(format!("failed to sign SIWE message: {:#}", e)))?;
     let sig_hex = sig.as_bytes().encode_hex();
 
 
@@ -173,14 +239,62 @@ fn authenticate(signer: &PrivateKeySigner, url: &str, client: reqwest::blocking:
     let res = client
         .post(format!("{}/siwe/tokens", url))
         .json(&payload)
-        .send().unwrap();
-    
-    
-    let response: serde_json::Value = res.json().unwrap();    
-    
+        .send()?;
+
+
     // 7. Get the JWT token from the response
-    let jwt_token = response["result"].as_str().ok_or("Failed to authenticate with server")?.to_string();
-    
-    
+    let jwt_token = parse_response(res)?
+        .as_str()
+        .ok_or_else(|| GardenError::MalformedResponse("Failed to authenticate with server".to_string()))?
+        .to_string();
+
+
     Ok(jwt_token)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: &str) -> String {
+        format!(
+            "{}.{}.signature",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[test]
+    fn jwt_expiry_reads_the_exp_claim() {
+        assert_eq!(jwt_expiry(&jwt(r#"{"sub":"0xabc","exp":1700000000}"#)), Some(1700000000));
+    }
+
+    #[test]
+    fn jwt_expiry_is_none_for_malformed_tokens_or_a_missing_claim() {
+        let cases = [
+            jwt(r#"{"sub":"0xabc"}"#),
+            jwt(r#"{"exp":"soon"}"#),
+            jwt("not json"),
+            "header.!!!.signature".to_string(),
+            "no-dots".to_string(),
+            String::new(),
+        ];
+        for token in cases {
+            assert_eq!(jwt_expiry(&token), None, "{}", token);
+        }
+    }
+
+    #[test]
+    fn session_is_expiring_within_the_refresh_margin() {
+        let session = Session::new(jwt(r#"{"exp":1700000000}"#));
+        assert!(!session.is_expiring_at(1700000000 - JWT_REFRESH_MARGIN - 1));
+        assert!(session.is_expiring_at(1700000000 - JWT_REFRESH_MARGIN));
+        assert!(session.is_expiring_at(1700000000 + 1));
+    }
+
+    #[test]
+    fn session_without_exp_never_expires() {
+        let session = Session::new(jwt(r#"{"sub":"0xabc"}"#));
+        assert!(!session.is_expiring_at(i64::MAX - JWT_REFRESH_MARGIN));
+    }
+}
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::Result;
 use bitcoin::hex::DisplayHex;
 use rand::TryRngCore;
 use serde_json::Value;
+use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
+use super::error::{self, from_value, parse_response, GardenError};
 use super::types::{Order, Strategy};
 
 #[derive(Debug, Clone)]
@@ -16,59 +18,58 @@ pub struct Quote {
 
 impl Quote {
     
-    pub fn new(client: reqwest::blocking::Client, url: String) -> Result<Self> {
-        let resp = client.get(format!("{}/strategies", url)).send().unwrap();
+    pub fn new(client: reqwest::blocking::Client, url: String) -> error::Result<Self> {
+        let resp = client.get(format!("{}/strategies", url)).send()?;
         
-        let response = resp.json::<Value>().map_err(|e| anyhow!("error fetching strategies from quote {}", e)).unwrap();
-        
-        let strategies: HashMap<String, Strategy> = serde_json::from_value(response["result"].clone())
-            .map_err(|e| anyhow!("error deserializing strategies: {}", e)).unwrap();
+        let strategies: HashMap<String, Strategy> = from_value(parse_response(resp)?)?;
         
         if !strategies.is_empty() {
             Ok(Self { client, url , strategies_map: strategies })
         } else {
-            bail!("no strategies found");
+            Err(GardenError::Api("no strategies found".to_string()))
         }
         
     }
     
-    pub  fn get_price(&self, order_pair: &str, amount: &str) -> Result<String> {
-        let url = format!("{}/price?order_pair={}&amount={}&exact_out={}", self.url, order_pair, amount, false);
+    /// Returns every solver's quote for the pair keyed by strategy id: the destination amount for
+    /// `amount` of the source asset, or with `exact_out` the source amount required to receive
+    /// `amount` of the destination asset.
+    pub  fn get_price(&self, order_pair: &str, amount: &str, exact_out: bool) -> error::Result<HashMap<String, String>> {
+        let url = format!("{}/price?order_pair={}&amount={}&exact_out={}", self.url, order_pair, amount, exact_out);
         
         let resp = self.client.get(&url).send()?;
         
-        let response = resp.json::<Value>().map_err(|e| anyhow!("error getting quote price {}", e))?;
-        let output_amount = response["result"]["quotes"].clone();
-        let final_price = output_amount
+        let result = parse_response(resp)?;
+        let quotes: HashMap<String, String> = result["quotes"]
             .as_object()
-            .ok_or_else(|| anyhow!("output amount is not an object"))?
-            .values()
-            .next()
-            .ok_or_else(|| anyhow!("output amount object is empty"))?
-            .to_string();
+            .ok_or_else(|| GardenError::MalformedResponse(format!("quotes is not an object in {}", result)))?
+            .iter()
+            .map(|(strategy_id, price)| {
+                let price = match price {
+                    Value::String(price) => price.clone(),
+                    other => other.to_string(),
+                };
+                (strategy_id.clone(), price)
+            })
+            .collect();
         
-        Ok(final_price.trim_matches('"').to_string())
+        if quotes.is_empty() {
+            return Err(GardenError::Api(format!("no quotes for {}", order_pair)));
+        }
+        Ok(quotes)
     }
     
-    pub  fn get_attested_quote(&self, order: Order) -> Result<Order> {
+    pub  fn get_attested_quote(&self, order: Order) -> error::Result<Order> {
         let url = format!("{}/attested", self.url);
         let resp = self.client.post(&url).json(&order).send()?;
         
-        let mut response = resp.json::<Value>()
-            .context("error getting attested quote")?;
-        
-        let Some(result) = response.get_mut("result") else {
-            bail!("missing result field in response {response}");
-        };
-        
-        let attested_order: Order = serde_json::from_value(result.take())
-            .context(format!("error parsing order from attested quote "))?;
-        
-        Ok(attested_order)
+        from_value(parse_response(resp)?)
     }
     
-    pub fn strategy_readable(&self, strategy_id: &str) -> Result<String> {
-        let strategy = self.strategies_map.get(strategy_id).expect("failed to retrieve strategy");
+    pub fn strategy_readable(&self, strategy_id: &str) -> error::Result<String> {
+        let strategy = self.strategies_map
+            .get(strategy_id)
+            .ok_or_else(|| GardenError::UnknownStrategy(strategy_id.to_string()))?;
         let readable_strat = format!("{} to {}", strategy.source_chain, strategy.dest_chain);
         Ok(readable_strat)
     }
@@ -87,4 +88,16 @@ pub fn generate_secret() -> Result<([u8; 32], [u8; 32])> {
     hash_array.copy_from_slice(&hash_bytes);
 
     Ok((secret, hash_array))
-}
\ No newline at end of file
+}
+
+/// Derives the secret of an order from a wallet key and the order's nonce, so it can be
+/// regenerated from the order alone. Returns the secret and its hash like `generate_secret`.
+pub fn derive_secret(key: &[u8], nonce: &str) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(b"garden-secret:");
+    mac.update(nonce.as_bytes());
+    let secret: [u8; 32] = mac.finalize().into_bytes().into();
+
+    let hash: [u8; 32] = Sha256::digest(secret).into();
+    (secret, hash)
+}
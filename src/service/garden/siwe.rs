@@ -0,0 +1,324 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use alloy::primitives::Address;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::{distr::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+
+const PREAMBLE: &str = " wants you to sign in with your Ethereum account:";
+
+/// Per network fields of the Sign-In with Ethereum message sent to the authenticator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiweConfig {
+    pub domain: String,
+    pub uri: String,
+    pub chain_id: u64,
+    pub statement: Option<String>,
+    /// How long the signed message stays valid, in seconds.
+    #[serde(default = "default_message_ttl")]
+    pub message_ttl: u64,
+}
+
+fn default_message_ttl() -> u64 {
+    300
+}
+
+/// An EIP-4361 message. `to_string` builds the text that gets signed and `parse` reads it back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: Address,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Message for `address` issued now, expiring after the configured ttl, with a random request id.
+    pub fn new(config: &SiweConfig, address: Address, nonce: &str) -> Self {
+        let issued_at = Utc::now();
+        let request_id: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+
+        SiweMessage {
+            domain: config.domain.clone(),
+            address,
+            statement: config.statement.clone(),
+            uri: config.uri.clone(),
+            version: "1".to_string(),
+            chain_id: config.chain_id,
+            nonce: nonce.to_string(),
+            issued_at,
+            expiration_time: Some(issued_at + Duration::seconds(config.message_ttl as i64)),
+            not_before: None,
+            request_id: Some(request_id),
+            resources: Vec::new(),
+        }
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl Display for SiweMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, PREAMBLE)?;
+        // `Address` displays with the EIP-55 checksum the spec requires
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", format_time(&self.issued_at))?;
+        if let Some(expiration_time) = &self.expiration_time {
+            write!(f, "\nExpiration Time: {}", format_time(expiration_time))?;
+        }
+        if let Some(not_before) = &self.not_before {
+            write!(f, "\nNot Before: {}", format_time(not_before))?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct SiweParseError(String);
+
+impl Display for SiweParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid SIWE message: {}", self.0)
+    }
+}
+
+impl std::error::Error for SiweParseError {}
+
+fn parse_error(message: impl Into<String>) -> SiweParseError {
+    SiweParseError(message.into())
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, SiweParseError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| parse_error(format!("bad timestamp {}: {}", value, e)))
+}
+
+/// Walks the message line by line, the fields have a fixed order.
+struct Lines<'a> {
+    lines: std::iter::Peekable<std::str::Split<'a, char>>,
+}
+
+impl<'a> Lines<'a> {
+    fn next(&mut self, what: &str) -> Result<&'a str, SiweParseError> {
+        self.lines
+            .next()
+            .ok_or_else(|| parse_error(format!("missing {}", what)))
+    }
+
+    fn blank(&mut self) -> Result<(), SiweParseError> {
+        match self.next("blank line")? {
+            "" => Ok(()),
+            line => Err(parse_error(format!("expected blank line, got {:?}", line))),
+        }
+    }
+
+    fn field(&mut self, tag: &str) -> Result<&'a str, SiweParseError> {
+        self.optional(tag)?
+            .ok_or_else(|| parse_error(format!("missing {}", tag)))
+    }
+
+    fn optional(&mut self, tag: &str) -> Result<Option<&'a str>, SiweParseError> {
+        let prefix = format!("{}: ", tag);
+        match self.lines.peek() {
+            Some(line) if line.starts_with(&prefix) => {
+                let line = self.lines.next().unwrap_or_default();
+                Ok(Some(&line[prefix.len()..]))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl FromStr for SiweMessage {
+    type Err = SiweParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = Lines {
+            lines: s.split('\n').peekable(),
+        };
+
+        let domain = lines
+            .next("preamble")?
+            .strip_suffix(PREAMBLE)
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| parse_error("bad preamble"))?
+            .to_string();
+
+        let address = lines.next("address")?;
+        let address = Address::parse_checksummed(address, None)
+            .map_err(|e| parse_error(format!("bad address {}: {}", address, e)))?;
+
+        lines.blank()?;
+        let statement = match lines.next("statement")? {
+            "" => None,
+            statement => {
+                lines.blank()?;
+                Some(statement.to_string())
+            }
+        };
+
+        let uri = lines.field("URI")?.to_string();
+        let version = lines.field("Version")?.to_string();
+        if version != "1" {
+            return Err(parse_error(format!("unsupported version {}", version)));
+        }
+        let chain_id = lines.field("Chain ID")?;
+        let chain_id = chain_id
+            .parse::<u64>()
+            .map_err(|e| parse_error(format!("bad chain id {}: {}", chain_id, e)))?;
+        let nonce = lines.field("Nonce")?.to_string();
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(parse_error(format!("nonce must be at least 8 alphanumeric characters, got {}", nonce)));
+        }
+        let issued_at = parse_time(lines.field("Issued At")?)?;
+        let expiration_time = lines.optional("Expiration Time")?.map(parse_time).transpose()?;
+        let not_before = lines.optional("Not Before")?.map(parse_time).transpose()?;
+        let request_id = lines.optional("Request ID")?.map(|id| id.to_string());
+
+        let mut resources = Vec::new();
+        if lines.lines.peek() == Some(&"Resources:") {
+            lines.lines.next();
+            while let Some(resource) = lines.lines.peek().and_then(|line| line.strip_prefix("- ")) {
+                resources.push(resource.to_string());
+                lines.lines.next();
+            }
+        }
+
+        if let Some(line) = lines.lines.next() {
+            return Err(parse_error(format!("unexpected line {:?}", line)));
+        }
+
+        Ok(SiweMessage {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> SiweMessage {
+        let config = SiweConfig {
+            domain: "testnet.garden.finance".to_string(),
+            uri: "https://testnet.garden.finance".to_string(),
+            chain_id: 11155111,
+            statement: Some("Garden.fi".to_string()),
+            message_ttl: 300,
+        };
+        let address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+        let mut message = SiweMessage::new(&config, address, "n0nce1234");
+        // the text only carries milliseconds, so round trips compare equal at that precision
+        message.issued_at = parse_time("2025-01-01T00:00:00.123Z").unwrap();
+        message.expiration_time = Some(parse_time("2025-01-01T00:05:00.123Z").unwrap());
+        message
+    }
+
+    fn replace_line(message: &SiweMessage, from: &str, to: &str) -> String {
+        message
+            .to_string()
+            .split('\n')
+            .map(|line| if line.starts_with(from) { to } else { line })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn round_trips_with_all_fields() {
+        let message = message();
+        assert_eq!(message.to_string().parse::<SiweMessage>().unwrap(), message);
+    }
+
+    #[test]
+    fn round_trips_without_optional_fields() {
+        let mut message = message();
+        message.statement = None;
+        message.expiration_time = None;
+        message.request_id = None;
+        assert_eq!(message.to_string().parse::<SiweMessage>().unwrap(), message);
+    }
+
+    #[test]
+    fn rejects_wrong_preamble() {
+        let text = replace_line(&message(), "testnet.garden.finance", "testnet.garden.finance wants you to sign in:");
+        assert!(text.parse::<SiweMessage>().is_err());
+    }
+
+    #[test]
+    fn rejects_missing_or_short_nonce() {
+        let message = message();
+        let short = replace_line(&message, "Nonce: ", "Nonce: abc");
+        assert!(short.parse::<SiweMessage>().is_err());
+
+        let missing = message
+            .to_string()
+            .split('\n')
+            .filter(|line| !line.starts_with("Nonce: "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(missing.parse::<SiweMessage>().is_err());
+    }
+
+    #[test]
+    fn rejects_bad_version() {
+        let text = replace_line(&message(), "Version: ", "Version: 2");
+        assert!(text.parse::<SiweMessage>().is_err());
+    }
+
+    #[test]
+    fn rejects_bad_chain_id() {
+        let text = replace_line(&message(), "Chain ID: ", "Chain ID: sepolia");
+        assert!(text.parse::<SiweMessage>().is_err());
+    }
+
+    #[test]
+    fn rejects_non_rfc3339_issued_at() {
+        let text = replace_line(&message(), "Issued At: ", "Issued At: 2025-01-01 00:00:00");
+        assert!(text.parse::<SiweMessage>().is_err());
+    }
+}
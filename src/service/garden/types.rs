@@ -1,5 +1,6 @@
 use std::fmt::Display;
-use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
+use std::str::FromStr;
+use bigdecimal::{num_bigint::Sign, BigDecimal, FromPrimitive, ToPrimitive, Zero};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +20,9 @@ pub struct AdditionalData {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Order {
+    /// Order id, only set on orders returned by the orderbook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create_id: Option<String>,
     pub source_chain: String,
     pub destination_chain: String,
     pub source_asset: String,
@@ -41,6 +45,7 @@ pub struct OrderInputData {
     pub in_amount: u64,
     pub out_amount: u64,
     pub secret_hash: String,
+    pub nonce: u64,
     pub strategy: Strategy,
     pub btc_opt_recipient: Option<String>
 }
@@ -48,6 +53,7 @@ pub struct OrderInputData {
 impl Order {
     pub fn new(order_data: OrderInputData) -> Self {
         Self { 
+            create_id: None,
             source_chain: order_data.strategy.source_chain, 
             destination_chain: order_data.strategy.dest_chain, 
             source_asset: order_data.strategy.source_asset.asset, 
@@ -57,7 +63,7 @@ impl Order {
             source_amount: BigDecimal::from_u64(order_data.in_amount).unwrap(), 
             destination_amount: BigDecimal::from_u64(order_data.out_amount).unwrap(), 
             fee: BigDecimal::from_u64(order_data.strategy.fee).unwrap(), 
-            nonce: BigDecimal::from_u64(100).unwrap(), 
+            nonce: BigDecimal::from_u64(order_data.nonce).unwrap(), 
             min_destination_confirmations: 1, 
             timelock: order_data.strategy.min_source_timelock*2, 
             secret_hash: order_data.secret_hash, 
@@ -80,6 +86,41 @@ pub struct Asset {
     pub decimals: u8,
 }
 
+impl Asset {
+    /// 10^decimals, the number of base units in one whole token.
+    fn unit(&self) -> BigDecimal {
+        BigDecimal::new(1.into(), -(self.decimals as i64))
+    }
+
+    /// Converts a decimal amount such as `0.005` into base units, rejecting amounts that
+    /// would have to be rounded or do not fit into a `u64`.
+    pub fn to_base_units(&self, amount: &str) -> Result<u64, String> {
+        let value = BigDecimal::from_str(amount.trim())
+            .map_err(|e| format!("invalid amount {}: {}", amount, e))?;
+        if value.sign() != Sign::Plus {
+            return Err("amount must be greater than zero".to_string());
+        }
+
+        let base_units = value * self.unit();
+        if !base_units.is_integer() {
+            return Err(format!("{} has more than {} decimals", amount, self.decimals));
+        }
+        base_units
+            .to_u64()
+            .ok_or_else(|| format!("amount {} is too large", amount))
+    }
+
+    /// Base units as whole tokens, e.g. `50000` with 8 decimals as `0.0005`.
+    pub fn to_whole_units(&self, base_units: &BigDecimal) -> BigDecimal {
+        base_units / self.unit()
+    }
+
+    /// Formats base units as a decimal amount, e.g. `50000` with 8 decimals as `0.0005`.
+    pub fn format_amount(&self, base_units: &BigDecimal) -> String {
+        self.to_whole_units(base_units).normalized().to_plain_string()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Strategy {
     pub id: String,
@@ -97,6 +138,57 @@ pub struct Strategy {
     pub min_price: f64,
     pub fee: u64, // in bips
 }
+impl Strategy {
+    /// Pair identifier expected by the quote server, e.g. `bitcoin_testnet:primary::arbitrum_sepolia:0x..`.
+    pub fn order_pair(&self) -> String {
+        format!(
+            "{}:{}::{}:{}",
+            self.source_chain, self.source_asset.asset, self.dest_chain, self.dest_asset.asset
+        )
+    }
+
+    /// Checks a source amount in base units against the strategy's `min_amount` and `max_amount`.
+    pub fn check_amount(&self, in_amount: u64) -> Result<(), String> {
+        let amount = BigDecimal::from(in_amount);
+        if amount < self.min_amount {
+            return Err(format!(
+                "amount {} is below the minimum of {}",
+                self.source_asset.format_amount(&amount),
+                self.source_asset.format_amount(&self.min_amount)
+            ));
+        }
+        if amount > self.max_amount {
+            return Err(format!(
+                "amount {} is above the maximum of {}",
+                self.source_asset.format_amount(&amount),
+                self.source_asset.format_amount(&self.max_amount)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Destination tokens received per source token, in whole units of each asset.
+    /// Zero when nothing is sent.
+    pub fn rate(&self, in_amount: &BigDecimal, out_amount: &BigDecimal) -> BigDecimal {
+        if in_amount.is_zero() {
+            return BigDecimal::zero();
+        }
+        let source = in_amount / self.source_asset.unit();
+        let dest = out_amount / self.dest_asset.unit();
+        dest / source
+    }
+
+    /// A warning when the quoted rate falls below the strategy's `min_price`.
+    pub fn price_warning(&self, in_amount: u64, out_amount: u64) -> Option<String> {
+        let rate = self.rate(&in_amount.into(), &out_amount.into());
+        // through the shortest decimal form, so `0.1` compares as 0.1 rather than its binary expansion
+        let min_price = BigDecimal::from_str(&self.min_price.to_string()).ok()?;
+        (rate < min_price).then(|| {
+            format!("rate {:.8} is below the strategy's min price {}", rate, self.min_price)
+        })
+    }
+}
+
 impl Display for Strategy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -156,6 +248,28 @@ pub struct MatchedOrder {
     pub create_order: Order,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One page of a paginated orderbook listing
+pub struct PaginatedData<T> {
+    pub data: Vec<T>,
+    pub page: u64,
+    pub total_pages: u64,
+    pub total_items: u64,
+    pub per_page: u64,
+}
+
+
+/// Base units of a quote. The quote server returns them as a decimal string that may carry
+/// a fractional part or an exponent, so they are read the same way order amounts are.
+pub fn parse_quote(price: &str) -> Result<u64, String> {
+    let decimal = BigDecimal::from_str(price.trim()).map_err(|e| format!("quote {} is not a number: {}", price, e))?;
+    if decimal.with_scale(0) != decimal {
+        return Err(format!("quote {} is not a whole number of base units", price));
+    }
+    decimal
+        .to_u64()
+        .ok_or_else(|| format!("quote {} is out of range", price))
+}
 
 pub fn big_decimal_to_i64(decimal: &BigDecimal) -> Result<i64, String> {
     if decimal.with_scale(0) != *decimal {
@@ -166,4 +280,120 @@ pub fn big_decimal_to_i64(decimal: &BigDecimal) -> Result<i64, String> {
     decimal.to_i64().ok_or_else(|| 
         format!("Value {} cannot be represented as i64", decimal)
     )
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(decimals: u8) -> Asset {
+        Asset {
+            asset: "primary".to_string(),
+            token_id: "bitcoin".to_string(),
+            decimals,
+        }
+    }
+
+    #[test]
+    fn to_base_units_parses_decimal_amounts() {
+        let cases: &[(&str, u8, u64)] = &[
+            ("0.1", 8, 10_000_000),
+            ("1", 8, 100_000_000),
+            ("1.", 8, 100_000_000),
+            (".5", 8, 50_000_000),
+            (" 0.00000001 ", 8, 1),
+            ("0.10000000", 8, 10_000_000),
+            ("18446744073709551615", 0, u64::MAX),
+            ("2.5", 6, 2_500_000),
+        ];
+        for (amount, decimals, expected) in cases {
+            let result = asset(*decimals).to_base_units(amount);
+            assert_eq!(result, Ok(*expected), "{} with {} decimals", amount, decimals);
+        }
+    }
+
+    #[test]
+    fn to_base_units_rejects_invalid_amounts() {
+        let cases: &[(&str, u8, &str)] = &[
+            ("0.000000001", 8, "more than 8 decimals"),
+            ("1.0000000001", 8, "more than 8 decimals"),
+            ("1.5", 0, "more than 0 decimals"),
+            ("", 8, "invalid amount"),
+            ("abc", 8, "invalid amount"),
+            ("0", 8, "greater than zero"),
+            ("-1", 8, "greater than zero"),
+            ("18446744073709551616", 0, "too large"),
+            ("184467440738", 8, "too large"),
+        ];
+        for (amount, decimals, message) in cases {
+            let err = asset(*decimals).to_base_units(amount).unwrap_err();
+            assert!(err.contains(message), "{:?}: {}", amount, err);
+        }
+    }
+
+    #[test]
+    fn format_amount_prints_whole_units() {
+        let cases: &[(u64, u8, &str)] = &[
+            (10_000_000, 8, "0.1"),
+            (50_000, 8, "0.0005"),
+            (100_000_000, 8, "1"),
+            (1, 18, "0.000000000000000001"),
+            (u64::MAX, 0, "18446744073709551615"),
+        ];
+        for (base_units, decimals, expected) in cases {
+            assert_eq!(asset(*decimals).format_amount(&BigDecimal::from(*base_units)), *expected);
+        }
+    }
+
+    fn strategy(min_price: f64) -> Strategy {
+        Strategy {
+            id: "btc-wbtc".to_string(),
+            source_chain_address: "primary".to_string(),
+            dest_chain_address: "0x00".to_string(),
+            source_chain: "bitcoin_testnet".to_string(),
+            dest_chain: "arbitrum_sepolia".to_string(),
+            source_asset: asset(8),
+            dest_asset: Asset {
+                asset: "0x00".to_string(),
+                token_id: "ethereum".to_string(),
+                decimals: 18,
+            },
+            makers: vec![],
+            min_amount: BigDecimal::from(50_000),
+            max_amount: BigDecimal::from(1_000_000),
+            min_source_timelock: 144,
+            min_source_confirmations: 1,
+            min_price,
+            fee: 30,
+        }
+    }
+
+    #[test]
+    fn check_amount_accepts_the_limits() {
+        let strategy = strategy(0.0);
+        assert!(strategy.check_amount(49_999).unwrap_err().contains("below the minimum of 0.0005"));
+        assert_eq!(strategy.check_amount(50_000), Ok(()));
+        assert_eq!(strategy.check_amount(1_000_000), Ok(()));
+        assert!(strategy.check_amount(1_000_001).unwrap_err().contains("above the maximum of 0.01"));
+    }
+
+    #[test]
+    fn rate_is_exact_for_large_amounts() {
+        let strategy = strategy(0.0);
+        // 0.01 BTC for 0.123456789012345678 ETH, more digits than an f64 holds
+        let rate = strategy.rate(&BigDecimal::from(1_000_000), &BigDecimal::from(123_456_789_012_345_678u64));
+        assert_eq!(rate, BigDecimal::from_str("12.3456789012345678").unwrap());
+        assert_eq!(format!("{:.8}", rate), "12.34567890");
+        assert_eq!(strategy.rate(&BigDecimal::zero(), &BigDecimal::from(1)), BigDecimal::zero());
+    }
+
+    #[test]
+    fn price_warning_below_min_price_only() {
+        let strategy = strategy(0.1);
+        // 1 BTC for exactly 0.1 ETH is at the min price
+        let at_min = 100_000_000_000_000_000;
+        assert_eq!(strategy.price_warning(100_000_000, at_min), None);
+        assert_eq!(strategy.price_warning(100_000_000, at_min + 1), None);
+        let warning = strategy.price_warning(100_000_000, at_min - 1).unwrap();
+        assert!(warning.contains("below the strategy's min price 0.1"), "{}", warning);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod blockchain;
-pub mod garden;
\ No newline at end of file
+pub mod garden;
+pub mod signer;
\ No newline at end of file
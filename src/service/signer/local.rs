@@ -0,0 +1,76 @@
+use alloy::{
+    primitives::{Address, B256},
+    signers::{local::PrivateKeySigner, Signature, SignerSync},
+};
+use anyhow::Result;
+use bitcoin::{
+    bip32::KeySource,
+    key::{Keypair, Secp256k1, TapTweak},
+    secp256k1::{ecdsa, schnorr, All, Message, SecretKey},
+};
+
+use super::{single_key_origin, WalletSigner};
+use crate::service::garden::quote::derive_secret;
+
+/// Signs with keys held in this process.
+pub struct LocalWalletSigner {
+    evm: PrivateKeySigner,
+    btc: Keypair,
+    btc_origin: Option<KeySource>,
+    secp: Secp256k1<All>,
+}
+
+impl LocalWalletSigner {
+    pub fn new(evm: PrivateKeySigner, btc: SecretKey) -> Self {
+        let secp = Secp256k1::new();
+        LocalWalletSigner {
+            evm,
+            btc: Keypair::from_secret_key(&secp, &btc),
+            btc_origin: None,
+            secp,
+        }
+    }
+
+    /// Records where the Bitcoin key was derived from, e.g. a seed phrase.
+    pub fn with_btc_origin(mut self, origin: Option<KeySource>) -> Self {
+        self.btc_origin = origin;
+        self
+    }
+}
+
+impl WalletSigner for LocalWalletSigner {
+    fn address(&self) -> Address {
+        self.evm.address()
+    }
+
+    fn btc_public_key(&self) -> bitcoin::PublicKey {
+        bitcoin::PublicKey::new(self.btc.public_key())
+    }
+
+    fn btc_key_origin(&self) -> KeySource {
+        self.btc_origin
+            .clone()
+            .unwrap_or_else(|| single_key_origin(&self.btc_public_key()))
+    }
+
+    fn sign_evm_hash(&self, hash: &B256) -> Result<Signature> {
+        Ok(self.evm.sign_hash_sync(hash)?)
+    }
+
+    fn sign_btc_ecdsa(&self, message: &Message) -> Result<ecdsa::Signature> {
+        Ok(self.secp.sign_ecdsa(message, &self.btc.secret_key()))
+    }
+
+    fn sign_btc_schnorr(&self, message: &Message) -> Result<schnorr::Signature> {
+        Ok(self.secp.sign_schnorr_no_aux_rand(message, &self.btc))
+    }
+
+    fn sign_btc_key_spend(&self, message: &Message) -> Result<schnorr::Signature> {
+        let tweaked = self.btc.tap_tweak(&self.secp, None).to_inner();
+        Ok(self.secp.sign_schnorr_no_aux_rand(message, &tweaked))
+    }
+
+    fn derive_secret(&self, nonce: &str) -> Result<([u8; 32], [u8; 32])> {
+        Ok(derive_secret(self.evm.to_bytes().as_slice(), nonce))
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use alloy::{
+    consensus::SignableTransaction,
+    network::TxSigner,
+    primitives::{eip191_hash_message, Address, B256},
+    signers::Signature,
+    sol_types::{Eip712Domain, SolStruct},
+};
+use anyhow::Result;
+use bitcoin::{
+    bip32::{DerivationPath, Fingerprint, KeySource},
+    hashes::Hash,
+    secp256k1::{ecdsa, schnorr, Message},
+};
+
+pub mod local;
+#[cfg(unix)]
+pub mod remote;
+
+/// Signs for both of the wallet's keys without handing them out, so they can live outside
+/// the TUI process. Everything is signed as a precomputed digest.
+pub trait WalletSigner: Send + Sync {
+    /// EVM address of the wallet.
+    fn address(&self) -> Address;
+
+    /// Compressed public key of the Bitcoin key.
+    fn btc_public_key(&self) -> bitcoin::PublicKey;
+
+    /// Master fingerprint and derivation path of the Bitcoin key, written to PSBTs so external
+    /// signers can find the key.
+    fn btc_key_origin(&self) -> KeySource;
+
+    /// Recoverable ECDSA signature over an EIP-191, EIP-712 or transaction hash.
+    fn sign_evm_hash(&self, hash: &B256) -> Result<Signature>;
+
+    /// ECDSA signature with the Bitcoin key, used by P2WPKH inputs.
+    fn sign_btc_ecdsa(&self, message: &Message) -> Result<ecdsa::Signature>;
+
+    /// BIP340 signature with the Bitcoin key, used by the HTLC's taproot leaves.
+    fn sign_btc_schnorr(&self, message: &Message) -> Result<schnorr::Signature>;
+
+    /// BIP340 signature with the Bitcoin key tweaked as in BIP86, used by key path spends of
+    /// the wallet's own taproot outputs.
+    fn sign_btc_key_spend(&self, message: &Message) -> Result<schnorr::Signature>;
+
+    /// Secret and secret hash of the order with `nonce`, derived from the EVM key.
+    fn derive_secret(&self, nonce: &str) -> Result<([u8; 32], [u8; 32])>;
+}
+
+pub type SharedSigner = Arc<dyn WalletSigner>;
+
+/// Origin of a key that is not derived from anything, it is its own master.
+pub fn single_key_origin(public_key: &bitcoin::PublicKey) -> KeySource {
+    let hash = public_key.pubkey_hash().to_byte_array();
+    (Fingerprint::from([hash[0], hash[1], hash[2], hash[3]]), DerivationPath::master())
+}
+
+impl dyn WalletSigner {
+    /// EIP-191 personal message signature.
+    pub fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        self.sign_evm_hash(&eip191_hash_message(message))
+    }
+
+    pub fn sign_typed_data<T: SolStruct>(&self, data: &T, domain: &Eip712Domain) -> Result<Signature> {
+        self.sign_evm_hash(&data.eip712_signing_hash(domain))
+    }
+}
+
+/// Lets alloy's `EthereumWallet` send transactions through a `WalletSigner`.
+pub struct EvmTxSigner(pub SharedSigner);
+
+#[async_trait::async_trait]
+impl TxSigner<Signature> for EvmTxSigner {
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy::signers::Result<Signature> {
+        self.0
+            .sign_evm_hash(&tx.signature_hash())
+            .map_err(alloy::signers::Error::other)
+    }
+}
@@ -0,0 +1,390 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use alloy::{
+    primitives::{Address, B256},
+    signers::Signature,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use bitcoin::bip32::{DerivationPath, Fingerprint, KeySource};
+use bitcoin::key::TapTweak;
+use bitcoin::secp256k1::{ecdsa, schnorr, Message, Secp256k1, VerifyOnly};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::{single_key_origin, WalletSigner};
+
+// long enough for a daemon that asks its operator to approve each signature
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SIGNING_FAILED: i64 = -32000;
+
+/// Signs through a daemon holding the keys, speaking newline delimited JSON-RPC 2.0 over a
+/// Unix socket. Every request opens its own connection, so the signer can be shared by threads.
+///
+/// Methods, with every byte string hex encoded:
+/// - `wallet_publicKeys` returns `{ "evm_address", "btc_public_key" }` and optionally
+///   `"btc_key_origin": { "fingerprint", "path" }`
+/// - `evm_signHash` takes `{ "hash" }` and returns the 65 byte `r || s || v` signature
+/// - `btc_signEcdsa` takes `{ "digest" }` and returns the 64 byte compact signature
+/// - `btc_signSchnorr` takes `{ "digest" }` and returns the 64 byte BIP340 signature
+/// - `btc_signKeySpend` takes `{ "digest" }` and returns the 64 byte BIP340 signature of the
+///   BIP86 tweaked key
+/// - `wallet_deriveSecret` takes `{ "nonce" }` and returns the 32 byte secret
+pub struct RemoteWalletSigner {
+    socket: PathBuf,
+    address: Address,
+    btc_public_key: bitcoin::PublicKey,
+    btc_key_origin: KeySource,
+    next_id: AtomicU64,
+    secp: Secp256k1<VerifyOnly>,
+}
+
+impl RemoteWalletSigner {
+    /// Connects to the daemon and fetches the wallet's public keys.
+    pub fn connect(socket: &Path) -> Result<Self> {
+        let keys = request(socket, 0, "wallet_publicKeys", json!({}))?;
+
+        let address = keys["evm_address"]
+            .as_str()
+            .ok_or_else(|| anyhow!("signer daemon returned no evm_address"))?;
+        let btc_public_key = keys["btc_public_key"]
+            .as_str()
+            .ok_or_else(|| anyhow!("signer daemon returned no btc_public_key"))?;
+
+        let btc_public_key = bitcoin::PublicKey::from_str(btc_public_key)
+            .context("signer daemon returned an invalid btc_public_key")?;
+        if !btc_public_key.compressed {
+            bail!("signer daemon returned an uncompressed btc_public_key");
+        }
+        let btc_key_origin = match keys.get("btc_key_origin") {
+            Some(origin) => {
+                let fingerprint = origin["fingerprint"].as_str().unwrap_or_default();
+                let path = origin["path"].as_str().unwrap_or_default();
+                (
+                    Fingerprint::from_str(fingerprint).context("signer daemon returned an invalid fingerprint")?,
+                    DerivationPath::from_str(path).context("signer daemon returned an invalid derivation path")?,
+                )
+            }
+            None => single_key_origin(&btc_public_key),
+        };
+
+        Ok(RemoteWalletSigner {
+            socket: socket.to_path_buf(),
+            address: Address::from_str(address).context("signer daemon returned an invalid evm_address")?,
+            btc_public_key,
+            btc_key_origin,
+            next_id: AtomicU64::new(1),
+            secp: Secp256k1::verification_only(),
+        })
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Vec<u8>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let result = request(&self.socket, id, method, params)?;
+        let result = result
+            .as_str()
+            .ok_or_else(|| anyhow!("signer daemon returned a non string result for {}", method))?;
+        hex::decode(result.trim_start_matches("0x"))
+            .with_context(|| format!("signer daemon returned invalid hex for {}", method))
+    }
+}
+
+fn request(socket: &Path, id: u64, method: &str, params: Value) -> Result<Value> {
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("Failed to connect to the signer daemon at {}", socket.display()))?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+    writeln!(stream, "{}", request)?;
+
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .with_context(|| format!("No response from the signer daemon for {}", method))?;
+    let mut response: Value = serde_json::from_str(&line)
+        .with_context(|| format!("Malformed response from the signer daemon for {}", method))?;
+
+    if let Some(error) = response.get("error") {
+        let message = error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+        bail!("signer daemon rejected {}: {}", method, message);
+    }
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => bail!("signer daemon returned no result for {}", method),
+    }
+}
+
+// a daemon that signs with the wrong key would only be caught by the chain, so check every signature
+impl WalletSigner for RemoteWalletSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn btc_public_key(&self) -> bitcoin::PublicKey {
+        self.btc_public_key
+    }
+
+    fn btc_key_origin(&self) -> KeySource {
+        self.btc_key_origin.clone()
+    }
+
+    fn sign_evm_hash(&self, hash: &B256) -> Result<Signature> {
+        let bytes = self.call("evm_signHash", json!({ "hash": hash.to_string() }))?;
+        let signature = Signature::try_from(bytes.as_slice())
+            .map_err(|e| anyhow!("signer daemon returned an invalid signature: {}", e))?;
+
+        let signer = signature
+            .recover_address_from_prehash(hash)
+            .map_err(|e| anyhow!("signer daemon returned an unrecoverable signature: {}", e))?;
+        if signer != self.address {
+            bail!("signer daemon signed with {} instead of {}", signer, self.address);
+        }
+        Ok(signature)
+    }
+
+    fn sign_btc_ecdsa(&self, message: &Message) -> Result<ecdsa::Signature> {
+        let bytes = self.call("btc_signEcdsa", json!({ "digest": hex::encode(&message[..]) }))?;
+        let signature = ecdsa::Signature::from_compact(&bytes)
+            .map_err(|e| anyhow!("signer daemon returned an invalid ECDSA signature: {}", e))?;
+
+        self.secp
+            .verify_ecdsa(message, &signature, &self.btc_public_key.inner)
+            .map_err(|_| anyhow!("signer daemon's ECDSA signature does not verify"))?;
+        Ok(signature)
+    }
+
+    fn sign_btc_schnorr(&self, message: &Message) -> Result<schnorr::Signature> {
+        let bytes = self.call("btc_signSchnorr", json!({ "digest": hex::encode(&message[..]) }))?;
+        let signature = schnorr::Signature::from_slice(&bytes)
+            .map_err(|e| anyhow!("signer daemon returned an invalid Schnorr signature: {}", e))?;
+
+        let (x_only, _) = self.btc_public_key.inner.x_only_public_key();
+        self.secp
+            .verify_schnorr(&signature, message, &x_only)
+            .map_err(|_| anyhow!("signer daemon's Schnorr signature does not verify"))?;
+        Ok(signature)
+    }
+
+    fn sign_btc_key_spend(&self, message: &Message) -> Result<schnorr::Signature> {
+        let bytes = self.call("btc_signKeySpend", json!({ "digest": hex::encode(&message[..]) }))?;
+        let signature = schnorr::Signature::from_slice(&bytes)
+            .map_err(|e| anyhow!("signer daemon returned an invalid Schnorr signature: {}", e))?;
+
+        let (x_only, _) = self.btc_public_key.inner.x_only_public_key();
+        let (output_key, _) = x_only.tap_tweak(&self.secp, None);
+        self.secp
+            .verify_schnorr(&signature, message, &output_key.to_inner())
+            .map_err(|_| anyhow!("signer daemon's key spend signature does not verify"))?;
+        Ok(signature)
+    }
+
+    fn derive_secret(&self, nonce: &str) -> Result<([u8; 32], [u8; 32])> {
+        let bytes = self.call("wallet_deriveSecret", json!({ "nonce": nonce }))?;
+        let secret: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("signer daemon returned a secret that is not 32 bytes"))?;
+        Ok((secret, Sha256::digest(secret).into()))
+    }
+}
+
+/// Serves `signer` on `socket` as the daemon `RemoteWalletSigner` talks to. Connections are
+/// handled one at a time until the process is stopped.
+pub fn serve(socket: &Path, signer: &dyn WalletSigner) -> Result<()> {
+    if let Ok(metadata) = fs::symlink_metadata(socket) {
+        if !metadata.file_type().is_socket() {
+            bail!("{} exists and is not a socket", socket.display());
+        }
+        fs::remove_file(socket)?;
+    }
+
+    let listener = bind_private(socket)?;
+
+    for stream in listener.incoming() {
+        let result = stream
+            .map_err(anyhow::Error::from)
+            .and_then(|stream| handle_connection(stream, signer));
+        if let Err(e) = result {
+            eprintln!("signer daemon: {:#}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Binds `socket` so that only the owner can ever connect, since anyone who can connect can
+/// sign. The socket is created inside a fresh 0700 directory, restricted to 0600 and only then
+/// moved into place, so it is never reachable with the permissions of the process umask.
+fn bind_private(socket: &Path) -> Result<UnixListener> {
+    let parent = socket
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let staging = parent.join(format!(".garden-signer-{}", std::process::id()));
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .with_context(|| format!("Failed to create {}", staging.display()))?;
+
+    let staged = staging.join("socket");
+    let result = UnixListener::bind(&staged)
+        .with_context(|| format!("Failed to listen on {}", socket.display()))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))?;
+            fs::rename(&staged, socket)
+                .with_context(|| format!("Failed to move the signer socket to {}", socket.display()))?;
+            Ok(listener)
+        });
+
+    let _ = fs::remove_file(&staged);
+    fs::remove_dir(&staging)?;
+    result
+}
+
+fn handle_connection(stream: UnixStream, signer: &dyn WalletSigner) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => {
+                let id = request["id"].clone();
+                let method = request["method"].as_str().unwrap_or_default();
+                match dispatch(signer, method, &request["params"]) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => {
+                        json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+                    }
+                }
+            }
+            Err(e) => json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": e.to_string() } }),
+        };
+        writeln!(writer, "{}", response)?;
+    }
+    Ok(())
+}
+
+fn dispatch(signer: &dyn WalletSigner, method: &str, params: &Value) -> std::result::Result<Value, (i64, String)> {
+    let digest = |name: &str| -> std::result::Result<[u8; 32], (i64, String)> {
+        let value = params[name]
+            .as_str()
+            .ok_or_else(|| (INVALID_PARAMS, format!("missing {}", name)))?;
+        hex::decode(value.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| (INVALID_PARAMS, format!("{} must be 32 hex encoded bytes", name)))
+    };
+    let signing_failed = |e: anyhow::Error| (SIGNING_FAILED, format!("{:#}", e));
+
+    match method {
+        "wallet_publicKeys" => {
+            let (fingerprint, path) = signer.btc_key_origin();
+            Ok(json!({
+                "evm_address": signer.address().to_string(),
+                "btc_public_key": signer.btc_public_key().to_string(),
+                "btc_key_origin": { "fingerprint": fingerprint.to_string(), "path": path.to_string() },
+            }))
+        }
+        "evm_signHash" => {
+            let signature = signer.sign_evm_hash(&B256::from(digest("hash")?)).map_err(signing_failed)?;
+            Ok(json!(hex::encode(signature.as_bytes())))
+        }
+        "btc_signEcdsa" => {
+            let message = Message::from_digest(digest("digest")?);
+            let signature = signer.sign_btc_ecdsa(&message).map_err(signing_failed)?;
+            Ok(json!(hex::encode(signature.serialize_compact())))
+        }
+        "btc_signSchnorr" => {
+            let message = Message::from_digest(digest("digest")?);
+            let signature = signer.sign_btc_schnorr(&message).map_err(signing_failed)?;
+            Ok(json!(hex::encode(signature.as_ref())))
+        }
+        "btc_signKeySpend" => {
+            let message = Message::from_digest(digest("digest")?);
+            let signature = signer.sign_btc_key_spend(&message).map_err(signing_failed)?;
+            Ok(json!(hex::encode(signature.as_ref())))
+        }
+        "wallet_deriveSecret" => {
+            let nonce = params["nonce"]
+                .as_str()
+                .ok_or_else(|| (INVALID_PARAMS, "missing nonce".to_string()))?;
+            let (secret, _) = signer.derive_secret(nonce).map_err(signing_failed)?;
+            Ok(json!(hex::encode(secret)))
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method {}", method))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Instant;
+
+    use alloy::signers::local::PrivateKeySigner;
+    use bitcoin::secp256k1::SecretKey;
+
+    use super::*;
+    use crate::service::signer::local::LocalWalletSigner;
+
+    fn local_signer() -> LocalWalletSigner {
+        let evm = PrivateKeySigner::from_bytes(&B256::repeat_byte(0x22)).unwrap();
+        let btc = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        LocalWalletSigner::new(evm, btc)
+    }
+
+    #[test]
+    fn remote_signer_round_trips_through_serve() {
+        let dir = std::env::temp_dir().join(format!("garden-signer-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("signer.sock");
+
+        let server_socket = socket.clone();
+        thread::spawn(move || serve(&server_socket, &local_signer()));
+
+        let started = Instant::now();
+        while !socket.exists() {
+            assert!(started.elapsed() < Duration::from_secs(5), "signer daemon did not start");
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mode = fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let local = local_signer();
+        let remote = RemoteWalletSigner::connect(&socket).unwrap();
+        assert_eq!(remote.address(), local.address());
+        assert_eq!(remote.btc_public_key(), local.btc_public_key());
+
+        let hash = B256::repeat_byte(0x33);
+        let signature = remote.sign_evm_hash(&hash).unwrap();
+        assert_eq!(signature.recover_address_from_prehash(&hash).unwrap(), local.address());
+
+        let secp = Secp256k1::verification_only();
+        let message = Message::from_digest([0x44; 32]);
+        let public_key = local.btc_public_key().inner;
+
+        let ecdsa = remote.sign_btc_ecdsa(&message).unwrap();
+        assert!(secp.verify_ecdsa(&message, &ecdsa, &public_key).is_ok());
+
+        let schnorr = remote.sign_btc_schnorr(&message).unwrap();
+        let (x_only, _) = public_key.x_only_public_key();
+        assert!(secp.verify_schnorr(&schnorr, &message, &x_only).is_ok());
+
+        let key_spend = remote.sign_btc_key_spend(&message).unwrap();
+        let (output_key, _) = x_only.tap_tweak(&secp, None);
+        assert!(secp.verify_schnorr(&key_spend, &message, &output_key.to_inner()).is_ok());
+
+        assert_eq!(remote.derive_secret("1").unwrap(), local.derive_secret("1").unwrap());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,963 @@
+use anyhow::{anyhow, Context, Result};
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::OutPoint;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::context::AppContext;
+use crate::service::blockchain::bitcoin::coin_selection::CoinSelector;
+use crate::service::blockchain::bitcoin::htlc::BitcoinHTLC;
+use crate::service::blockchain::bitcoin::htlc_handler::HtlcHandler;
+use crate::service::blockchain::bitcoin::psbt;
+use crate::service::blockchain::evm;
+use crate::service::blockchain::evm::init_and_get_sig;
+use crate::service::blockchain::evm::Initiate;
+use crate::service::garden::quote::generate_secret;
+use crate::service::garden::types::big_decimal_to_i64;
+use crate::service::garden::types::InitiateRequest;
+use crate::service::garden::types::MatchedOrder;
+use crate::service::garden::types::Order;
+use crate::service::garden::types::OrderInputData;
+use crate::service::garden::types::SingleSwap;
+use crate::service::garden::types::Strategy;
+use crate::service::signer::WalletSigner;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderProgress {
+    NotStarted,
+    OrderCreated,
+    Initialized,
+    DestinationInitialized,
+    Redeemed,
+    Refunded,
+    Failed(String),
+}
+
+/// How long a new order may take to be matched by a solver before initiating gives up.
+pub const MATCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often the orderbook is polled while waiting on the destination chain.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long a Bitcoin transaction may stay unconfirmed before the dashboard offers a fee bump.
+pub const STUCK_TX_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// Longest an unattended driver waits in a step before it gives up and hands control back.
+pub fn step_timeout(progress: &OrderProgress) -> Duration {
+    match progress {
+        OrderProgress::NotStarted => Duration::from_secs(2 * 60),
+        OrderProgress::OrderCreated => Duration::from_secs(10 * 60),
+        // covers source confirmations plus the solver's destination init
+        OrderProgress::Initialized => Duration::from_secs(2 * 60 * 60),
+        OrderProgress::DestinationInitialized => Duration::from_secs(10 * 60),
+        _ => Duration::MAX,
+    }
+}
+
+/// The step's timeout once `elapsed` in it has gone past it, `None` while the driver may wait.
+pub fn exceeded_step_timeout(progress: &OrderProgress, elapsed: Duration) -> Option<Duration> {
+    let timeout = step_timeout(progress);
+    (elapsed > timeout).then_some(timeout)
+}
+
+/// Result of a single swap step, applied by the caller to its own view of the order.
+pub struct StepOutcome {
+    pub progress: OrderProgress,
+    pub status: String,
+    pub order_id: Option<String>,
+    pub matched_order: Option<MatchedOrder>,
+}
+
+
+fn has_tx(hash: &Option<String>) -> bool {
+    hash.as_deref().is_some_and(|hash| !hash.is_empty())
+}
+
+/// Where an order stands according to the orderbook, used to continue orders that were not
+/// started from this session.
+pub fn infer_progress(matched_order: &MatchedOrder) -> OrderProgress {
+    let source = &matched_order.source_swap;
+    let destination = &matched_order.destination_swap;
+
+    if has_tx(&source.refund_tx_hash) {
+        OrderProgress::Refunded
+    } else if has_tx(&destination.redeem_tx_hash) {
+        OrderProgress::Redeemed
+    } else if has_tx(&destination.initiate_tx_hash)
+        && destination.current_confirmations >= destination.required_confirmations
+    {
+        OrderProgress::DestinationInitialized
+    } else if has_tx(&source.initiate_tx_hash) {
+        OrderProgress::Initialized
+    } else {
+        OrderProgress::OrderCreated
+    }
+}
+
+/// Whether `secret` hashes to the order's secret hash.
+pub fn secret_matches(order: &Order, secret: &[u8; 32]) -> bool {
+    hex::encode(Sha256::digest(secret)) == order.secret_hash.trim_start_matches("0x").to_lowercase()
+}
+
+/// Reads a 32 byte secret from hex, with or without a `0x` prefix.
+pub fn parse_secret(input: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(input.trim().trim_start_matches("0x")).context("The secret is not valid hex")?;
+    <[u8; 32]>::try_from(bytes).map_err(|_| anyhow!("The secret must be 32 bytes"))
+}
+
+/// Regenerates the secret of an order created with deterministic secrets from this wallet.
+/// Returns `None` when the derived secret does not match the order's secret hash.
+pub fn recover_secret(signer: &dyn WalletSigner, order: &Order) -> Option<[u8; 32]> {
+    let (secret, _) = derive_order_secret(signer, &order.nonce).ok()?;
+    secret_matches(order, &secret).then_some(secret)
+}
+
+/// Derives an order's secret from its nonce. The orderbook may hand the nonce back with a
+/// scale (`1700000000000.0`), so it is normalised to the integer `build_order` derived from.
+fn derive_order_secret(signer: &dyn WalletSigner, nonce: &BigDecimal) -> Result<([u8; 32], [u8; 32])> {
+    let nonce = nonce
+        .is_integer()
+        .then(|| nonce.to_u64())
+        .flatten()
+        .ok_or_else(|| anyhow!("The order nonce {} is not an integer", nonce))?;
+    signer.derive_secret(&nonce.to_string())
+}
+
+/// Builds an unattested order for `strategy` from the wallet's addresses and a new secret,
+/// derived from the wallet key and the order nonce when deterministic secrets are enabled.
+pub fn build_order(context: &AppContext, strategy: &Strategy, in_amount: u64, out_amount: u64) -> Result<(Order, [u8; 32])> {
+    let (init_src_add, init_dest_addr, btc_opt_recp ) = if strategy.source_chain.contains("bitcoin") {
+        (context.wallet.btc_xpubkey.to_string(), context.wallet.signer.address().to_string(), None)
+    } else if strategy.dest_chain.contains("bitcoin") {
+        
+        (context.wallet.signer.address().to_string(), context.wallet.btc_xpubkey.to_string(), Some(context.wallet.btc_address.clone()))
+    } else {
+        (context.wallet.signer.address().to_string(), context.wallet.signer.address().to_string(), None)
+    };
+    
+    let nonce = chrono::Utc::now().timestamp_millis() as u64;
+    let (secret, secret_hash) = if context.deterministic_secrets {
+        derive_order_secret(context.wallet.signer.as_ref(), &BigDecimal::from(nonce))?
+    } else {
+        generate_secret()?
+    };
+    let order = Order::new(OrderInputData{
+        initiator_source_address: init_src_add,
+        initiator_dest_address: init_dest_addr,
+        in_amount,
+        out_amount, 
+        secret_hash: hex::encode(secret_hash),
+        nonce,
+        strategy: strategy.clone(),
+        btc_opt_recipient: btc_opt_recp
+    });
+
+    Ok((order, secret))
+}
+
+pub fn create_order(context: &AppContext) -> Result<StepOutcome> {
+    let order = context
+        .order
+        .current_order
+        .clone()
+        .ok_or_else(|| anyhow!("No current order available"))?;
+
+    let order_id = context
+        .api
+        .orderbook
+        .create_order(order)
+        .context("Failed to create order")?;
+
+    // give the solver a moment to match the order before it is initiated
+    sleep(Duration::from_secs(5));
+
+    Ok(StepOutcome {
+        progress: OrderProgress::OrderCreated,
+        status: "Order Created. Press 's' to initialize".to_string(),
+        order_id: Some(order_id.trim_matches('"').to_string()),
+        matched_order: None,
+    })
+}
+
+pub fn initiate_source(context: &AppContext, order_id: &str) -> Result<StepOutcome> {
+    // the solver may still be matching the order that was just created
+    let matched_order = context
+        .api
+        .orderbook
+        .wait_for_matched_order(order_id, MATCH_TIMEOUT)
+        .context("Failed to get matched order")?;
+    let swap = &matched_order.source_swap;
+
+    let tx = if swap.chain.contains("bitcoin") {
+        initiate_bitcoin_swap(swap, context)
+    } else {
+        initiate_evm_swap(swap, order_id, context)
+    }
+    .context("Failed to initiate source swap")?;
+
+    Ok(StepOutcome {
+        progress: OrderProgress::Initialized,
+        status: format!("Initialized. tx {} Press 's' to wait for destination", tx),
+        order_id: None,
+        matched_order: Some(matched_order),
+    })
+}
+
+fn initiate_bitcoin_swap(swap: &SingleSwap, context: &AppContext) -> Result<String> {
+    let htlc_handler = htlc_handler(context)?;
+
+    let htlc = BitcoinHTLC::new(
+        hex::decode(&swap.secret_hash)?,
+        swap.initiator.clone(),
+        swap.redeemer.clone(),
+        swap.timelock,
+        context.wallet.btc_network,
+    )?;
+    let amount = big_decimal_to_i64(&swap.amount).map_err(|e| anyhow!(e))?;
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+    let fee_rate = runtime.block_on(htlc_handler.fee_rate(context.fee_priority))?;
+
+    // lock the inputs until the broadcast, so a PSBT exported meanwhile doesn't pick them
+    let tx = context.journal.lock_utxos(&swap.secret_hash, |locked| {
+        let selector = coin_selector(context, locked);
+        let tx = runtime.block_on(htlc_handler.initaite_htlc(
+            context.wallet.signer.as_ref(),
+            htlc.address()?,
+            amount,
+            fee_rate,
+            &selector,
+        ))?;
+        let inputs = tx.input.iter().map(|input| input.previous_output.to_string()).collect();
+        Ok((tx, inputs))
+    })?;
+
+    let txid = runtime.block_on(htlc_handler.broadcast_tx(&tx))?;
+    context.journal.release_utxos(&swap.secret_hash)?;
+    Ok(txid)
+}
+
+fn initiate_evm_swap(swap: &SingleSwap, order_id: &str, context: &AppContext) -> Result<String> {
+    let init_data = Initiate::try_from(swap)?;
+    let provider_url = evm_provider(&swap.chain, context)?;
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+
+    let signature = runtime.block_on(init_and_get_sig(
+        init_data,
+        provider_url,
+        context.wallet.signer.clone(),
+        &swap.asset,
+    ))?;
+
+    let init_req = InitiateRequest {
+        order_id: order_id.to_string(),
+        signature: signature.to_string(),
+        perform_on: "Source".to_string(),
+    };
+
+    Ok(context.api.orderbook.initiate(init_req)?)
+}
+
+pub fn wait_for_destination(context: &AppContext, order_id: &str) -> Result<StepOutcome> {
+    let tx = context
+        .api
+        .orderbook
+        .wait_for_destination_init(order_id)
+        .context("Failed waiting for destination")?;
+
+    Ok(StepOutcome {
+        progress: OrderProgress::DestinationInitialized,
+        status: format!("Destination initialized in tx {}. Press 's' to redeem", tx),
+        order_id: None,
+        matched_order: None,
+    })
+}
+
+/// Single non-blocking check used by unattended drivers in place of `wait_for_destination`.
+pub fn poll_destination(context: &AppContext, order_id: &str) -> Result<StepOutcome> {
+    let matched_order = context
+        .api
+        .orderbook
+        .get_matched_order(order_id)
+        .context("Failed to get matched order")?;
+
+    let source = &matched_order.source_swap;
+    let destination = &matched_order.destination_swap;
+
+    let (progress, status) = match destination.initiate_tx_hash.as_deref() {
+        Some(hash) if !hash.is_empty() => {
+            if destination.current_confirmations < destination.required_confirmations {
+                (
+                    OrderProgress::Initialized,
+                    format!(
+                        "Destination initiated in tx {}, confirmations {}/{}",
+                        hash, destination.current_confirmations, destination.required_confirmations
+                    ),
+                )
+            } else {
+                (
+                    OrderProgress::DestinationInitialized,
+                    format!("Destination initialized in tx {}", hash),
+                )
+            }
+        }
+        _ => (
+            OrderProgress::Initialized,
+            format!(
+                "Waiting for destination init, source confirmations {}/{}",
+                source.current_confirmations, source.required_confirmations
+            ),
+        ),
+    };
+
+    Ok(StepOutcome {
+        progress,
+        status,
+        order_id: None,
+        matched_order: Some(matched_order),
+    })
+}
+
+pub fn redeem_destination(context: &AppContext, order_id: &str) -> Result<StepOutcome> {
+    let order = context
+        .order
+        .current_order
+        .as_ref()
+        .ok_or_else(|| anyhow!("No current order available"))?;
+    if !secret_matches(order, &context.order.secret) {
+        return Err(anyhow!("The secret of order {} is unknown, it is needed to redeem", order_id));
+    }
+
+    let matched_order = context
+        .api
+        .orderbook
+        .get_matched_order(order_id)
+        .context("Failed to get matched order")?;
+
+    let tx = if order.destination_chain.contains("bitcoin") {
+        let swap = &matched_order.destination_swap;
+        let htlc = BitcoinHTLC::new(
+            hex::decode(&swap.secret_hash)?,
+            swap.initiator.clone(),
+            swap.redeemer.clone(),
+            swap.timelock,
+            context.wallet.btc_network,
+        )?;
+        let witness_stack = htlc.redeem(&context.order.secret.to_vec())?;
+        let htlc_handler = htlc_handler(context)?;
+
+        let runtime = tokio::runtime::Runtime::new()
+            .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+
+        let tx = runtime.block_on(async {
+            let fee_rate = htlc_handler.fee_rate(context.fee_priority).await?;
+            htlc_handler
+                .create_redeem_tx(
+                    htlc.address()?,
+                    witness_stack,
+                    order.additional_data.bitcoin_optional_recipient.clone(),
+                    context.wallet.signer.as_ref(),
+                    fee_rate,
+                )
+                .await
+        })?;
+
+        context.api.orderbook.btc_redeem(order_id, &serialize_hex(&tx))
+    } else {
+        context.api.orderbook.redeem(order_id, &hex::encode(context.order.secret))
+    }
+    .context("Redeem failed")?;
+
+    if tx.is_empty() {
+        return Err(anyhow!("Redeem returned empty transaction"));
+    }
+
+    Ok(StepOutcome {
+        progress: OrderProgress::Redeemed,
+        status: format!("Redeem Successful!! {} ", tx),
+        order_id: None,
+        matched_order: Some(matched_order),
+    })
+}
+
+/// Refunds the source swap. With the redeemer's `counterparty_signature` an EVM swap is
+/// refunded through `instantRefund` right away instead of waiting for its timelock.
+pub fn refund_source(context: &AppContext, order_id: &str, counterparty_signature: Option<&str>) -> Result<StepOutcome> {
+    let matched_order = context
+        .api
+        .orderbook
+        .get_matched_order(order_id)
+        .context("Failed to get matched order")?;
+    let swap = matched_order.source_swap.clone();
+
+    let status = match swap.refund_tx_hash.as_deref() {
+        Some(hash) if !hash.is_empty() => format!("Already refunded in tx {}", hash),
+        _ => {
+            let tx = match (swap.chain.contains("bitcoin"), counterparty_signature) {
+                (true, Some(_)) => return Err(anyhow!("instant refunds are only supported on EVM chains")),
+                (true, None) => refund_bitcoin_swap(swap, context)?,
+                (false, Some(signature)) => instant_refund_evm_swap(swap, signature, context)?,
+                (false, None) => refund_evm_swap(swap, context)?,
+            };
+            format!("Refund broadcast in tx {}", tx)
+        }
+    };
+
+    Ok(StepOutcome {
+        progress: OrderProgress::Refunded,
+        status,
+        order_id: None,
+        matched_order: Some(matched_order),
+    })
+}
+
+fn refund_bitcoin_swap(swap: SingleSwap, context: &AppContext) -> Result<String> {
+    let secret_hash_bytes = hex::decode(&swap.secret_hash)?;
+    let htlc = BitcoinHTLC::new(
+        secret_hash_bytes,
+        swap.initiator,
+        swap.redeemer,
+        swap.timelock,
+        context.wallet.btc_network,
+    )?;
+
+    let htlc_handler = htlc_handler(context)?;
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+
+    runtime.block_on(async {
+        let fee_rate = htlc_handler.fee_rate(context.fee_priority).await?;
+        let tx = htlc_handler
+            .create_refund_tx(&htlc, context.wallet.signer.as_ref(), fee_rate)
+            .await?;
+        htlc_handler.broadcast_tx(&tx).await
+    })
+}
+
+fn refund_evm_swap(swap: SingleSwap, context: &AppContext) -> Result<String> {
+    let provider_url = evm_provider(&swap.chain, context)?;
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+
+    let tx_hash = runtime.block_on(evm::refund(
+        &swap.swap_id,
+        provider_url,
+        context.wallet.signer.clone(),
+        &swap.asset,
+    ))?;
+
+    Ok(tx_hash.to_string())
+}
+
+fn instant_refund_evm_swap(swap: SingleSwap, counterparty_signature: &str, context: &AppContext) -> Result<String> {
+    let provider_url = evm_provider(&swap.chain, context)?;
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+
+    let tx_hash = runtime.block_on(evm::instant_refund(
+        &swap.swap_id,
+        provider_url,
+        context.wallet.signer.clone(),
+        &swap.asset,
+        counterparty_signature,
+    ))?;
+
+    Ok(tx_hash.to_string())
+}
+
+/// Bitcoin transaction of a swap that can be signed outside the app as a PSBT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PsbtAction {
+    Initiate,
+    Redeem,
+    Refund,
+}
+
+impl PsbtAction {
+    // the wallet funds and refunds the source swap and redeems the destination one
+    fn swap<'a>(&self, matched_order: &'a MatchedOrder) -> &'a SingleSwap {
+        match self {
+            PsbtAction::Initiate | PsbtAction::Refund => &matched_order.source_swap,
+            PsbtAction::Redeem => &matched_order.destination_swap,
+        }
+    }
+}
+
+fn swap_htlc(swap: &SingleSwap, context: &AppContext) -> Result<BitcoinHTLC> {
+    if !swap.chain.contains("bitcoin") {
+        return Err(anyhow!("swap {} is on {}, not bitcoin", swap.swap_id, swap.chain));
+    }
+    BitcoinHTLC::new(
+        hex::decode(&swap.secret_hash)?,
+        swap.initiator.clone(),
+        swap.redeemer.clone(),
+        swap.timelock,
+        context.wallet.btc_network,
+    )
+}
+
+/// Unsigned PSBT of `action` on order `order_id`, base64 encoded.
+pub fn export_psbt(context: &AppContext, order_id: &str, action: PsbtAction) -> Result<String> {
+    let matched_order = context
+        .api
+        .orderbook
+        .get_matched_order(order_id)
+        .context("Failed to get matched order")?;
+    let swap = action.swap(&matched_order);
+    let htlc = swap_htlc(swap, context)?;
+
+    let htlc_handler = htlc_handler(context)?;
+    let signer = context.wallet.signer.as_ref();
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+    let fee_rate = runtime.block_on(htlc_handler.fee_rate(context.fee_priority))?;
+
+    let psbt = match action {
+        PsbtAction::Initiate => {
+            let amount = big_decimal_to_i64(&swap.amount).map_err(|e| anyhow!(e))?;
+            // keep other orders from spending the inputs while the PSBT is out for signing
+            context.journal.lock_utxos(&swap.secret_hash, |locked| {
+                let selector = coin_selector(context, locked);
+                let psbt = runtime.block_on(htlc_handler.initiate_htlc_psbt(signer, htlc.address()?, amount, fee_rate, &selector))?;
+                let inputs = psbt
+                    .unsigned_tx
+                    .input
+                    .iter()
+                    .map(|input| input.previous_output.to_string())
+                    .collect();
+                Ok((psbt, inputs))
+            })?
+        }
+        PsbtAction::Redeem | PsbtAction::Refund => {
+            if action == PsbtAction::Redeem {
+                let recipient = context
+                    .order
+                    .current_order
+                    .as_ref()
+                    .and_then(|order| order.additional_data.bitcoin_optional_recipient.clone());
+                runtime.block_on(htlc_handler.create_redeem_psbt(&htlc, recipient, signer, fee_rate))?
+            } else {
+                runtime.block_on(htlc_handler.create_refund_psbt(&htlc, signer, fee_rate))?
+            }
+        }
+    };
+
+    Ok(psbt::encode(&psbt))
+}
+
+/// Finalizes a PSBT signed outside the app and broadcasts it. The PSBT must pay into, or
+/// spend from, the HTLC of the order's swap.
+pub fn import_psbt(context: &AppContext, order_id: &str, action: PsbtAction, signed: &str) -> Result<StepOutcome> {
+    let matched_order = context
+        .api
+        .orderbook
+        .get_matched_order(order_id)
+        .context("Failed to get matched order")?;
+    let swap = action.swap(&matched_order);
+    let htlc = swap_htlc(swap, context)?;
+    let htlc_spk = htlc.address()?.script_pubkey();
+    let psbt = psbt::decode(signed)?;
+
+    let tx = match action {
+        PsbtAction::Initiate => {
+            let amount = big_decimal_to_i64(&swap.amount).map_err(|e| anyhow!(e))?;
+            let funds_htlc = psbt.unsigned_tx.output.iter().any(|output| {
+                output.script_pubkey == htlc_spk && output.value.to_sat() as i64 == amount
+            });
+            if !funds_htlc {
+                return Err(anyhow!("PSBT does not pay {} sats to the swap's HTLC", amount));
+            }
+            psbt::finalize_funding(psbt)?
+        }
+        PsbtAction::Redeem | PsbtAction::Refund => {
+            // a refund spends every matured HTLC output, a redeem only the funding one
+            let spends_htlc = !psbt.inputs.is_empty()
+                && psbt.inputs.iter().all(|input| {
+                    input
+                        .witness_utxo
+                        .as_ref()
+                        .is_some_and(|utxo| utxo.script_pubkey == htlc_spk)
+                });
+            if !spends_htlc {
+                return Err(anyhow!("PSBT does not spend from the swap's HTLC"));
+            }
+
+            let witness_stack = if action == PsbtAction::Redeem {
+                let order = context
+                    .order
+                    .current_order
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No current order available"))?;
+                if !secret_matches(order, &context.order.secret) {
+                    return Err(anyhow!("The secret of order {} is unknown, it is needed to redeem", order_id));
+                }
+                htlc.redeem(&context.order.secret.to_vec())?
+            } else {
+                htlc.refund()?
+            };
+            psbt::finalize_leaf_spend(psbt, &witness_stack)?
+        }
+    };
+
+    let (progress, tx) = match action {
+        PsbtAction::Redeem => {
+            let tx = context
+                .api
+                .orderbook
+                .btc_redeem(order_id, &serialize_hex(&tx))
+                .context("Redeem failed")?;
+            (OrderProgress::Redeemed, tx)
+        }
+        _ => {
+            let htlc_handler = htlc_handler(context)?;
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+            let tx = runtime.block_on(htlc_handler.broadcast_tx(&tx))?;
+            let progress = if action == PsbtAction::Initiate {
+                // the inputs are spent now, whether or not the order has a journal entry
+                context.journal.release_utxos(&swap.secret_hash)?;
+                OrderProgress::Initialized
+            } else {
+                OrderProgress::Refunded
+            };
+            (progress, tx)
+        }
+    };
+
+    Ok(StepOutcome {
+        progress,
+        status: format!("{:?} broadcast in tx {}", action, tx),
+        order_id: None,
+        matched_order: Some(matched_order),
+    })
+}
+
+/// Bitcoin transaction of an order whose fee can be raised while it is unconfirmed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BumpTarget {
+    Funding,
+    Redeem,
+}
+
+/// Replaces the order's unconfirmed funding or redeem transaction with one paying the current
+/// fee priority's rate and broadcasts it. Returns the replacement's txid.
+pub fn bump_fee(context: &AppContext, order_id: &str, target: BumpTarget) -> Result<String> {
+    let matched_order = context
+        .api
+        .orderbook
+        .get_matched_order(order_id)
+        .context("Failed to get matched order")?;
+    let swap = match target {
+        BumpTarget::Funding => &matched_order.source_swap,
+        BumpTarget::Redeem => &matched_order.destination_swap,
+    };
+    // the dashboard offers the bump from an older snapshot of the order
+    let confirmed = match target {
+        BumpTarget::Funding => swap.current_confirmations > 0,
+        BumpTarget::Redeem => swap.redeem_block_number.as_ref().is_some_and(|block| !block.is_zero()),
+    };
+    if confirmed {
+        return Err(anyhow!("the {:?} transaction of order {} is already confirmed", target, order_id));
+    }
+    let htlc = swap_htlc(swap, context)?;
+
+    let witness_stack = match target {
+        BumpTarget::Funding => None,
+        BumpTarget::Redeem => {
+            let order = context
+                .order
+                .current_order
+                .as_ref()
+                .ok_or_else(|| anyhow!("No current order available"))?;
+            if !secret_matches(order, &context.order.secret) {
+                return Err(anyhow!("The secret of order {} is unknown, it is needed to re-sign the redeem", order_id));
+            }
+            Some(htlc.redeem(&context.order.secret.to_vec())?)
+        }
+    };
+
+    let htlc_handler = htlc_handler(context)?;
+    let signer = context.wallet.signer.as_ref();
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| anyhow!("Unable to create runtime: {}", e))?;
+
+    runtime.block_on(async {
+        let fee_rate = htlc_handler.fee_rate(context.fee_priority).await?;
+        let tx = match witness_stack {
+            None => htlc_handler.bump_funding_tx(&htlc.address()?, signer, fee_rate).await?,
+            Some(witness_stack) => htlc_handler.bump_redeem_tx(&htlc, witness_stack, signer, fee_rate).await?,
+        };
+        htlc_handler.broadcast_tx(&tx).await
+    })
+}
+
+/// Coin selection for funding an HTLC, avoiding the `locked` outputs other orders' exported
+/// PSBTs are spending.
+fn coin_selector(context: &AppContext, locked: &[String]) -> CoinSelector {
+    let locked = locked
+        .iter()
+        .filter_map(|outpoint| OutPoint::from_str(outpoint).ok())
+        .collect::<Vec<_>>();
+
+    CoinSelector::new(context.coin_selection.strategy)
+        .wallet_script(context.wallet.btc_script)
+        .confirmed_only(context.coin_selection.confirmed_only)
+        .lock(locked)
+}
+
+/// HTLC handler for `context`'s network and the wallet's address type.
+fn htlc_handler(context: &AppContext) -> Result<HtlcHandler> {
+    Ok(HtlcHandler::new(context.wallet.btc_network, bitcoin_provider(context)?)?.with_wallet_script(context.wallet.btc_script))
+}
+
+pub fn bitcoin_provider(context: &AppContext) -> Result<&str> {
+    context
+        .wallet
+        .provider_urls
+        .get("bitcoin")
+        .map(|url| url.as_str())
+        .ok_or_else(|| anyhow!("no bitcoin provider configured"))
+}
+
+// provider urls are keyed by the chain family, e.g. `arbitrum` for `arbitrum_sepolia`
+pub fn evm_provider<'a>(chain: &str, context: &'a AppContext) -> Result<&'a str> {
+    let (family, _) = chain
+        .split_once("_")
+        .ok_or_else(|| anyhow!("unexpected chain name {}", chain))?;
+
+    context
+        .wallet
+        .provider_urls
+        .get(family)
+        .map(|url| url.as_str())
+        .ok_or_else(|| anyhow!("no provider configured for {}", family))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::B256, signers::local::PrivateKeySigner};
+    use bitcoin::secp256k1::SecretKey;
+
+    use super::*;
+    use crate::service::garden::quote::derive_secret;
+    use crate::service::garden::types::Asset;
+    use crate::service::signer::local::LocalWalletSigner;
+
+    fn signer() -> LocalWalletSigner {
+        let evm = PrivateKeySigner::from_bytes(&B256::repeat_byte(0x22)).unwrap();
+        let btc = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        LocalWalletSigner::new(evm, btc)
+    }
+
+    fn asset(chain: &str) -> Asset {
+        Asset {
+            asset: "primary".to_string(),
+            token_id: chain.to_string(),
+            decimals: 8,
+        }
+    }
+
+    fn order(nonce: u64, secret_hash: [u8; 32]) -> Order {
+        Order::new(OrderInputData {
+            initiator_source_address: "source".to_string(),
+            initiator_dest_address: "destination".to_string(),
+            in_amount: 100_000,
+            out_amount: 99_000,
+            secret_hash: hex::encode(secret_hash),
+            nonce,
+            strategy: Strategy {
+                id: "btcarb".to_string(),
+                source_chain_address: String::new(),
+                dest_chain_address: String::new(),
+                source_chain: "bitcoin_testnet".to_string(),
+                dest_chain: "arbitrum_sepolia".to_string(),
+                source_asset: asset("bitcoin"),
+                dest_asset: asset("wbtc"),
+                makers: Vec::new(),
+                min_amount: BigDecimal::from(1),
+                max_amount: BigDecimal::from(u64::MAX),
+                min_source_timelock: 144,
+                min_source_confirmations: 1,
+                min_price: 0.0,
+                fee: 30,
+            },
+            btc_opt_recipient: None,
+        })
+    }
+
+    fn swap(chain: &str) -> SingleSwap {
+        SingleSwap {
+            created_at: String::new(),
+            updated_at: String::new(),
+            deleted_at: None,
+            swap_id: format!("{}-swap", chain),
+            chain: chain.to_string(),
+            asset: "primary".to_string(),
+            initiator: "initiator".to_string(),
+            redeemer: "redeemer".to_string(),
+            timelock: 144,
+            filled_amount: BigDecimal::zero(),
+            amount: BigDecimal::from(100_000),
+            secret_hash: "00".repeat(32),
+            secret: None,
+            initiate_tx_hash: None,
+            redeem_tx_hash: None,
+            refund_tx_hash: None,
+            initiate_block_number: None,
+            redeem_block_number: None,
+            refund_block_number: None,
+            required_confirmations: 1,
+            current_confirmations: 0,
+        }
+    }
+
+    /// Matched order with a tx hash set for each of `txs`, e.g. `src_init` or `dst_redeem`.
+    /// `dst_confirmed` gives the destination initiate its required confirmations.
+    fn matched_order(txs: &[&str]) -> MatchedOrder {
+        let mut source = swap("bitcoin_testnet");
+        let mut destination = swap("arbitrum_sepolia");
+        for tx in txs {
+            let hash = Some(format!("0x{}", tx));
+            match *tx {
+                "src_init" => source.initiate_tx_hash = hash,
+                "src_redeem" => source.redeem_tx_hash = hash,
+                "src_refund" => source.refund_tx_hash = hash,
+                "dst_init" => destination.initiate_tx_hash = hash,
+                "dst_confirmed" => destination.current_confirmations = destination.required_confirmations,
+                "dst_redeem" => destination.redeem_tx_hash = hash,
+                "dst_refund" => destination.refund_tx_hash = hash,
+                other => panic!("unknown tx {}", other),
+            }
+        }
+        MatchedOrder {
+            created_at: String::new(),
+            updated_at: String::new(),
+            deleted_at: None,
+            source_swap: source,
+            destination_swap: destination,
+            create_order: order(1, [0; 32]),
+        }
+    }
+
+    #[test]
+    fn infer_progress_follows_the_tx_hashes_of_both_swaps() {
+        use OrderProgress::*;
+        let cases: &[(&[&str], OrderProgress)] = &[
+            (&[], OrderCreated),
+            (&["src_init"], Initialized),
+            // the destination only counts once it has its confirmations
+            (&["dst_init"], OrderCreated),
+            (&["src_init", "dst_init"], Initialized),
+            (&["src_init", "dst_init", "dst_confirmed"], DestinationInitialized),
+            (&["src_init", "dst_init", "dst_confirmed", "dst_redeem"], Redeemed),
+            (&["src_init", "dst_init", "dst_confirmed", "dst_redeem", "src_redeem"], Redeemed),
+            (&["src_init", "dst_redeem"], Redeemed),
+            // the solver redeeming the source does not finish the order without our redeem
+            (&["src_init", "dst_init", "dst_confirmed", "src_redeem"], DestinationInitialized),
+            (&["src_init", "src_refund"], Refunded),
+            (&["src_init", "dst_init", "dst_confirmed", "dst_refund", "src_refund"], Refunded),
+            (&["src_init", "dst_init", "dst_confirmed", "dst_refund"], DestinationInitialized),
+            (&["src_refund", "dst_redeem"], Refunded),
+        ];
+        for (txs, expected) in cases {
+            assert_eq!(&infer_progress(&matched_order(txs)), expected, "{:?}", txs);
+        }
+    }
+
+    #[test]
+    fn infer_progress_ignores_empty_tx_hashes() {
+        let mut order = matched_order(&["src_init"]);
+        order.source_swap.refund_tx_hash = Some(String::new());
+        order.destination_swap.redeem_tx_hash = Some(String::new());
+        assert_eq!(infer_progress(&order), OrderProgress::Initialized);
+
+        order.source_swap.initiate_tx_hash = Some(String::new());
+        assert_eq!(infer_progress(&order), OrderProgress::OrderCreated);
+    }
+
+    #[test]
+    fn steps_time_out_only_after_their_limit() {
+        let cases = [
+            (OrderProgress::NotStarted, 2 * 60),
+            (OrderProgress::OrderCreated, 10 * 60),
+            (OrderProgress::Initialized, 2 * 60 * 60),
+            (OrderProgress::DestinationInitialized, 10 * 60),
+        ];
+        for (progress, secs) in cases {
+            let limit = Duration::from_secs(secs);
+            assert_eq!(exceeded_step_timeout(&progress, Duration::ZERO), None, "{:?}", progress);
+            assert_eq!(exceeded_step_timeout(&progress, limit), None, "{:?}", progress);
+            let over = limit + Duration::from_millis(1);
+            assert_eq!(exceeded_step_timeout(&progress, over), Some(limit), "{:?}", progress);
+        }
+    }
+
+    #[test]
+    fn finished_and_failed_orders_never_time_out() {
+        for progress in [OrderProgress::Redeemed, OrderProgress::Refunded, OrderProgress::Failed("rpc".to_string())] {
+            assert_eq!(exceeded_step_timeout(&progress, Duration::from_secs(365 * 24 * 60 * 60)), None);
+        }
+    }
+
+    #[test]
+    fn derive_secret_is_deterministic_for_a_key_and_nonce() {
+        let (secret, hash) = derive_secret(&[0x22; 32], "1700000000000");
+        assert_eq!(derive_secret(&[0x22; 32], "1700000000000"), (secret, hash));
+        assert_eq!(hash, <[u8; 32]>::from(Sha256::digest(secret)));
+        assert_ne!(derive_secret(&[0x22; 32], "1700000000001").0, secret);
+        assert_ne!(derive_secret(&[0x23; 32], "1700000000000").0, secret);
+    }
+
+    #[test]
+    fn recover_secret_round_trips_the_derived_secret() {
+        let signer = signer();
+        let nonce = 1_700_000_000_000;
+        // derived the way build_order does it
+        let (secret, secret_hash) = derive_order_secret(&signer, &BigDecimal::from(nonce)).unwrap();
+        let mut order = order(nonce, secret_hash);
+        assert_eq!(recover_secret(&signer, &order), Some(secret));
+
+        // the orderbook may return the nonce with a scale
+        order.nonce = BigDecimal::from_str("1700000000000.0").unwrap();
+        assert_eq!(recover_secret(&signer, &order), Some(secret));
+
+        order.nonce = BigDecimal::from(nonce + 1);
+        assert_eq!(recover_secret(&signer, &order), None);
+        order.nonce = BigDecimal::from_str("1700000000000.5").unwrap();
+        assert_eq!(recover_secret(&signer, &order), None);
+    }
+
+    #[test]
+    fn parse_secret_accepts_32_byte_hex() {
+        let hex = "ab".repeat(32);
+        assert_eq!(parse_secret(&hex).unwrap(), [0xab; 32]);
+        assert_eq!(parse_secret(&format!(" 0x{} ", hex)).unwrap(), [0xab; 32]);
+    }
+
+    #[test]
+    fn parse_secret_rejects_bad_hex_and_wrong_lengths() {
+        let cases: &[(String, &str)] = &[
+            ("zz".repeat(32), "not valid hex"),
+            ("abc".to_string(), "not valid hex"),
+            ("ab".repeat(31), "must be 32 bytes"),
+            ("ab".repeat(33), "must be 32 bytes"),
+            (String::new(), "must be 32 bytes"),
+        ];
+        for (input, message) in cases {
+            let err = parse_secret(input).unwrap_err().to_string();
+            assert!(err.contains(message), "{:?}: {}", input, err);
+        }
+    }
+}
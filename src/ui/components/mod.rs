@@ -1 +1,2 @@
-pub mod selector;
\ No newline at end of file
+pub mod selector;
+pub mod task;
@@ -14,6 +14,10 @@ impl<T: Clone> Selector<T> {
             .map(|(id, item)| (id.clone(), item.clone()))
             .collect();
         
+        Self::from_items(items)
+    }
+
+    pub fn from_items(items: Vec<(String, T)>) -> Self {
         let mut state = ListState::default();
 
         if !items.is_empty() {
@@ -0,0 +1,77 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Runs blocking work on a background thread and hands the result back over a channel,
+/// so states can keep redrawing while network calls are in flight.
+pub struct Task<T> {
+    sender: Sender<Result<T>>,
+    receiver: Receiver<Result<T>>,
+    running: Option<(String, Instant)>,
+}
+
+impl<T: Send + 'static> Task<T> {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Task {
+            sender,
+            receiver,
+            running: None,
+        }
+    }
+
+    /// Starts `job` unless another job is still running. Returns whether the job was started.
+    pub fn spawn<F>(&mut self, label: &str, job: F) -> bool
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+    {
+        if self.is_running() {
+            return false;
+        }
+
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            // a panicking job is reported as an error instead of leaving the task running forever
+            let result = panic::catch_unwind(AssertUnwindSafe(job)).unwrap_or_else(|payload| {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                Err(anyhow!("background task panicked: {}", message))
+            });
+            let _ = sender.send(result);
+        });
+
+        self.running = Some((label.to_string(), Instant::now()));
+        true
+    }
+
+    /// Returns the job's result once it has finished, without blocking.
+    pub fn poll(&mut self) -> Option<Result<T>> {
+        let result = self.receiver.try_recv().ok()?;
+        self.running = None;
+        Some(result)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.running.as_ref().map(|(_, started)| started.elapsed())
+    }
+
+    /// Spinner frame, label and elapsed seconds of the running job, e.g. `⠹ Creating order (3s)`.
+    pub fn progress_line(&self) -> Option<String> {
+        let (label, _) = self.running.as_ref()?;
+        let elapsed = self.elapsed()?;
+        let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+        Some(format!("{} {} ({}s)", frame, label, elapsed.as_secs()))
+    }
+}
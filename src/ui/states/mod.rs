@@ -1,20 +1,30 @@
 use crossterm::event::KeyEvent;
 use ratatui::Frame;
 
-use crate::context::AppContext;
+use crate::{context::AppContext, journal::JournalEntry, service::garden::types::MatchedOrder};
 
 pub mod network_information;
 pub mod swap_information;
 pub mod order_information;
+pub mod resume_swap;
+pub mod order_history;
 
 pub enum StateType {
     NetworkInformation,
     SwapInformation,
     OrderInformation,
+    ResumeOrder(Box<JournalEntry>),
+    OrderHistory,
+    OpenOrder(Box<MatchedOrder>),
     Quit,
 }
 
 pub trait State {
     fn draw(&self, frame: &mut Frame, context: &mut AppContext);
     fn handle_key(&mut self, key: KeyEvent, context: &mut AppContext) -> Option<StateType>;
+
+    /// Called on every event loop tick to collect results from background tasks.
+    fn tick(&mut self, _context: &mut AppContext) -> Option<StateType> {
+        None
+    }
 }
\ No newline at end of file
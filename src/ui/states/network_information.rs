@@ -22,6 +22,17 @@ impl NetworkInformationState {
     }
 }
 
+fn address_line(label: &'static str, address: String, path: Option<String>) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled(label, Style::default().fg(Color::Yellow)),
+        Span::styled(address, Style::default().fg(Color::White)),
+    ];
+    if let Some(path) = path {
+        spans.push(Span::styled(format!(" ({})", path), Style::default().fg(Color::DarkGray)));
+    }
+    Line::from(spans)
+}
+
 impl State for NetworkInformationState {
     fn draw(&self, frame: &mut Frame, context: &mut AppContext) {
         let size = frame.area();
@@ -43,7 +54,7 @@ impl State for NetworkInformationState {
             .margin(2)
             .constraints([
                 Constraint::Length(3),   // Title
-                Constraint::Length(5),   // Network Info - increased height
+                Constraint::Length(6),   // Network Info - increased height
                 Constraint::Length(10),  // Strategy Selector
                 Constraint::Length(3),   // Selected Strategy (if any)
                 Constraint::Min(0),      // Instructions
@@ -63,7 +74,7 @@ impl State for NetworkInformationState {
             // Network info
             let info_text = vec![
                 Line::from(vec![
-                    Span::styled(format!("Selected Network: "),
+                    Span::styled("Selected Network: ",
                         Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
                     Span::styled(context.selected_network.clone(), Style::default().fg(Color::White))
                 ]),
@@ -72,6 +83,16 @@ impl State for NetworkInformationState {
                         Style::default().fg(Color::Yellow)),
                     Span::styled(context.api.quote.url.clone(), Style::default().fg(Color::White))
                 ]),
+                address_line(
+                    "EVM Address: ",
+                    context.wallet.signer.address().to_string(),
+                    context.wallet.derivation.as_ref().map(|d| d.evm_path()),
+                ),
+                address_line(
+                    "BTC Address: ",
+                    context.wallet.btc_address.clone(),
+                    context.wallet.derivation.as_ref().map(|d| d.btc_path()),
+                ),
             ];
 
             let info_block = Block::default()
@@ -140,6 +161,7 @@ impl State for NetworkInformationState {
         let instructions_spans = vec![
             Span::styled("↑/↓: Navigate | ", Style::default().fg(Color::Red)),
             Span::styled("Enter: Select Strategy | ", Style::default().fg(Color::Red)),
+            Span::styled("h: Order History | ", Style::default().fg(Color::Green)),
             Span::styled("b: Back | ", Style::default().fg(Color::Red)),
             Span::styled("q: Quit", Style::default().fg(Color::Red)),
         ];
@@ -154,6 +176,7 @@ impl State for NetworkInformationState {
     fn handle_key(&mut self, key: KeyEvent, context: &mut AppContext) -> Option<StateType> {
         match key.code {
             KeyCode::Char('q') => Some(StateType::Quit),
+            KeyCode::Char('h') => Some(StateType::OrderHistory),
             KeyCode::Up => {
                 self.order_pair_selector.previous();
                 None
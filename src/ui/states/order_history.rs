@@ -0,0 +1,356 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout}, style::{Color, Modifier, Style}, text::{Line, Span}, widgets::{Block, Borders, List, ListItem, Paragraph}, Frame
+};
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{
+    context::AppContext,
+    journal::JournalEntry,
+    service::garden::types::{Asset, MatchedOrder, PaginatedData, SingleSwap},
+    swap::{infer_progress, recover_secret},
+    ui::components::{selector::Selector, task::Task},
+};
+use super::{State, StateType};
+
+const PER_PAGE: u64 = 10;
+
+enum HistoryJob {
+    Page(PaginatedData<MatchedOrder>),
+    /// Order looked up by the id typed into the open order prompt.
+    Order(String, Option<Box<MatchedOrder>>),
+}
+
+pub struct OrderHistoryState {
+    order_selector: Selector<MatchedOrder>,
+    page: u64,
+    total_pages: u64,
+    total_items: u64,
+    /// Order id being typed, the prompt is open while this is set.
+    id_input: Option<String>,
+    task: Task<HistoryJob>,
+    status: Option<String>,
+}
+
+impl OrderHistoryState {
+    pub fn new(context: &AppContext) -> Self {
+        let mut state = OrderHistoryState {
+            order_selector: Selector::from_items(Vec::new()),
+            page: 1,
+            total_pages: 1,
+            total_items: 0,
+            id_input: None,
+            task: Task::new(),
+            status: None,
+        };
+        state.load(context, 1);
+        state
+    }
+
+    /// Fetches a page of the wallet's orders in the background.
+    fn load(&mut self, context: &AppContext, page: u64) {
+        let orderbook = context.api.orderbook.clone();
+        let address = context.wallet.signer.address().to_string();
+        self.task.spawn(&format!("Loading page {}", page), move || {
+            Ok(HistoryJob::Page(orderbook.get_user_orders(&address, page, PER_PAGE)?))
+        });
+    }
+
+    /// Looks up an order by id, which need not belong to this wallet's history page.
+    fn find(&mut self, context: &AppContext, order_id: String) {
+        let orderbook = context.api.orderbook.clone();
+        self.task.spawn(&format!("Looking up order {}", order_id), move || {
+            let matched_order = orderbook.find_matched_order(&order_id)?;
+            Ok(HistoryJob::Order(order_id, matched_order.map(Box::new)))
+        });
+    }
+
+    fn handle_id_input(&mut self, key: KeyEvent, context: &AppContext) {
+        let Some(input) = self.id_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.id_input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_alphanumeric() => input.push(c),
+            KeyCode::Enter => {
+                let order_id = input.trim().to_string();
+                if order_id.is_empty() || self.task.is_running() {
+                    return;
+                }
+                self.id_input = None;
+                self.find(context, order_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Loads an order into the dashboard, with its secret if the journal has it or it can be
+    /// derived from the wallet key.
+    fn open(&mut self, matched_order: MatchedOrder, context: &mut AppContext) -> Option<StateType> {
+        let Some(order_id) = matched_order.create_order.create_id.clone() else {
+            self.status = Some("The orderbook returned this order without an id".to_string());
+            return None;
+        };
+
+        context.order.current_strategy = Some(matched_order.create_order.additional_data.strategy_id.clone());
+        context.order.current_order = Some(matched_order.create_order.clone());
+        context.order.secret = [0; 32];
+
+        match context.journal.find_by_order_id(&order_id) {
+            Ok(Some(mut entry)) => {
+                if let Ok(secret) = entry.secret_bytes() {
+                    context.order.secret = secret;
+                }
+                entry.update(&order_id, &infer_progress(&matched_order), Some(&matched_order));
+                Some(StateType::ResumeOrder(Box::new(entry)))
+            }
+            _ => match recover_secret(context.wallet.signer.as_ref(), &matched_order.create_order) {
+                Some(secret) => {
+                    context.order.secret = secret;
+                    let mut entry = JournalEntry::new(matched_order.create_order.clone(), secret);
+                    entry.update(&order_id, &infer_progress(&matched_order), Some(&matched_order));
+                    if let Err(e) = context.journal.record(entry.clone()) {
+                        self.status = Some(format!("Failed to journal the recovered secret: {:#}", e));
+                    }
+                    Some(StateType::ResumeOrder(Box::new(entry)))
+                }
+                None => Some(StateType::OpenOrder(Box::new(matched_order))),
+            },
+        }
+    }
+}
+
+fn tx(hash: &Option<String>) -> String {
+    match hash.as_deref() {
+        Some(hash) if !hash.is_empty() => hash.to_string(),
+        _ => "-".to_string(),
+    }
+}
+
+fn swap_lines(label: &str, swap: &SingleSwap, asset: Option<&Asset>) -> Vec<Line<'static>> {
+    let amount = match asset {
+        Some(asset) => asset.format_amount(&swap.amount),
+        None => swap.amount.to_string(),
+    };
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("  {:<10}", name), Style::default().fg(Color::Yellow)),
+            Span::raw(value),
+        ])
+    };
+
+    vec![
+        Line::from(Span::styled(
+            format!("{} {} {}", label, swap.chain, amount),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        field("initiate", format!(
+            "{} ({}/{} confs)",
+            tx(&swap.initiate_tx_hash),
+            swap.current_confirmations,
+            swap.required_confirmations
+        )),
+        field("redeem", tx(&swap.redeem_tx_hash)),
+        field("refund", tx(&swap.refund_tx_hash)),
+    ]
+}
+
+impl State for OrderHistoryState {
+    fn draw(&self, frame: &mut Frame, context: &mut AppContext) {
+        let size = frame.area();
+
+        let title_span = Span::styled(
+            "Order History",
+            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+        );
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(3),   // Title
+                Constraint::Min(6),      // Orders
+                Constraint::Length(10),  // Selected order
+                Constraint::Length(3),   // Status
+                Constraint::Length(1),   // Instructions
+            ].as_ref())
+            .split(size);
+
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(vec![title_span])])
+                .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::White)))
+                .alignment(Alignment::Center),
+            chunks[0],
+        );
+
+        let items: Vec<ListItem> = self.order_selector.items
+            .iter()
+            .map(|(id, matched_order)| {
+                ListItem::new(format!("{} | {} to {} | {:?} | {}",
+                    id,
+                    matched_order.source_swap.chain,
+                    matched_order.destination_swap.chain,
+                    infer_progress(matched_order),
+                    matched_order.created_at))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default()
+                .title(format!("Orders of {} (page {} of {})",
+                    context.wallet.signer.address(),
+                    self.page,
+                    self.total_pages.max(1)))
+                .borders(Borders::ALL))
+            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+
+        frame.render_stateful_widget(list, chunks[1], &mut self.order_selector.state.clone());
+
+        let details = match self.order_selector.selected_item() {
+            Some((_, matched_order)) => {
+                let strategy = context
+                    .api
+                    .quote
+                    .strategies_map
+                    .get(&matched_order.create_order.additional_data.strategy_id);
+                let mut lines = swap_lines(
+                    "Source",
+                    &matched_order.source_swap,
+                    strategy.map(|strategy| &strategy.source_asset),
+                );
+                lines.extend(swap_lines(
+                    "Destination",
+                    &matched_order.destination_swap,
+                    strategy.map(|strategy| &strategy.dest_asset),
+                ));
+                lines
+            }
+            None => vec![Line::from("No orders")],
+        };
+
+        frame.render_widget(
+            Paragraph::new(details)
+                .block(Block::default().title("Selected Order").borders(Borders::ALL))
+                .alignment(Alignment::Left),
+            chunks[2],
+        );
+
+        if let Some(input) = &self.id_input {
+            frame.render_widget(
+                Paragraph::new(input.clone())
+                    .block(Block::default()
+                        .title("Order ID (Enter: Open | Esc: Cancel)")
+                        .borders(Borders::ALL)
+                        .style(Style::default().fg(Color::Yellow)))
+                    .alignment(Alignment::Left),
+                chunks[3],
+            );
+            frame.set_cursor_position((chunks[3].x + 1 + input.len() as u16, chunks[3].y + 1));
+        } else {
+            let status_message = match (self.task.progress_line(), &self.status) {
+                (Some(line), _) => line,
+                (None, Some(msg)) => msg.clone(),
+                (None, None) => format!("{} orders", self.total_items),
+            };
+
+            frame.render_widget(
+                Paragraph::new(status_message)
+                    .block(Block::default().title("Status").borders(Borders::ALL))
+                    .alignment(Alignment::Left),
+                chunks[3],
+            );
+        }
+
+        let instructions_spans = vec![
+            Span::styled("↑/↓: Navigate | ", Style::default().fg(Color::Red)),
+            Span::styled("←/→: Page | ", Style::default().fg(Color::Red)),
+            Span::styled("Enter: Open Order | ", Style::default().fg(Color::Green)),
+            Span::styled("o: Open By ID | ", Style::default().fg(Color::Green)),
+            Span::styled("r: Reload | ", Style::default().fg(Color::Yellow)),
+            Span::styled("b: Back | ", Style::default().fg(Color::Red)),
+            Span::styled("q: Quit", Style::default().fg(Color::Red)),
+        ];
+
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(instructions_spans)])
+                .alignment(Alignment::Center),
+            chunks[4],
+        );
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, context: &mut AppContext) -> Option<StateType> {
+        if self.id_input.is_some() {
+            self.handle_id_input(key, context);
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => Some(StateType::Quit),
+            KeyCode::Char('b') => Some(StateType::NetworkInformation),
+            KeyCode::Up => {
+                self.order_selector.previous();
+                None
+            },
+            KeyCode::Down => {
+                self.order_selector.next();
+                None
+            },
+            KeyCode::Left => {
+                if self.page > 1 {
+                    self.load(context, self.page - 1);
+                }
+                None
+            },
+            KeyCode::Right => {
+                if self.page < self.total_pages {
+                    self.load(context, self.page + 1);
+                }
+                None
+            },
+            KeyCode::Char('r') => {
+                self.load(context, self.page);
+                None
+            },
+            KeyCode::Char('o') => {
+                self.id_input = Some(String::new());
+                None
+            },
+            KeyCode::Enter => {
+                let matched_order = self.order_selector.selected_item()?.1.clone();
+                self.open(matched_order, context)
+            },
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self, context: &mut AppContext) -> Option<StateType> {
+        match self.task.poll()? {
+            Ok(HistoryJob::Order(_, Some(matched_order))) => return self.open(*matched_order, context),
+            Ok(HistoryJob::Order(order_id, None)) => {
+                self.status = Some(format!("Order {} was not found or is not matched yet", order_id));
+            }
+            Ok(HistoryJob::Page(page)) => {
+                let items = page
+                    .data
+                    .into_iter()
+                    .map(|matched_order| {
+                        let id = matched_order.create_order.create_id.clone().unwrap_or_default();
+                        (id, matched_order)
+                    })
+                    .collect();
+                self.order_selector = Selector::from_items(items);
+                self.page = page.page;
+                self.total_pages = page.total_pages;
+                self.total_items = page.total_items;
+                self.status = None;
+            }
+            Err(e) => {
+                self.status = Some(format!("Failed to load orders: {:#}", e));
+            }
+        }
+        None
+    }
+}
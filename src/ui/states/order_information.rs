@@ -1,37 +1,36 @@
-use anyhow::anyhow;
-use bitcoin::consensus::encode::serialize_hex;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::Block;
 use ratatui::widgets::Borders;
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
-use std::thread::sleep;
-use std::time::Duration;
+use std::time::Instant;
 
 use crate::context::AppContext;
-use crate::service::blockchain::bitcoin::htlc::BitcoinHTLC;
-use crate::service::blockchain::bitcoin::htlc_handler::HtlcHandler;
-use crate::service::blockchain::evm::init_and_get_sig;
-use crate::service::blockchain::evm::Initiate;
-use crate::service::garden::types::big_decimal_to_i64;
-use crate::service::garden::types::InitiateRequest;
+use crate::journal::JournalEntry;
+use crate::service::garden::error::GardenError;
+use crate::service::garden::types::MatchedOrder;
+use crate::swap::{
+    bump_fee, create_order, infer_progress, initiate_source, parse_secret, poll_destination,
+    redeem_destination, refund_source, secret_matches, exceeded_step_timeout, wait_for_destination, BumpTarget, OrderProgress,
+    StepOutcome, POLL_INTERVAL, STUCK_TX_AFTER,
+};
+use crate::ui::components::task::Task;
 
 use super::{State, StateType};
 
-pub enum OrderProgress {
-    NotStarted,
-    OrderCreated,
-    Initialized,
-    DestinationInitialized,
-    Redeemed,
-    Failed(String),
-}
-
 pub struct OrderDashboardState {
     pub order_id: String,
     pub status: Option<String>,
     pub progress: OrderProgress,
+    pub matched_order: Option<MatchedOrder>,
+    task: Task<StepOutcome>,
+    auto: bool,
+    step_started: Instant,
+    last_poll: Option<Instant>,
+    /// Hex secret being typed, the prompt is open while this is set.
+    secret_input: Option<String>,
+    last_bump: Option<Instant>,
 }
 
 impl OrderDashboardState {
@@ -40,6 +39,341 @@ impl OrderDashboardState {
             order_id: "Press 's' to create-order".to_string(),
             status: None,
             progress: OrderProgress::NotStarted,
+            matched_order: None,
+            task: Task::new(),
+            auto: false,
+            step_started: Instant::now(),
+            last_poll: None,
+            secret_input: None,
+            last_bump: None,
+        }
+    }
+
+    pub fn resume(entry: JournalEntry) -> Self {
+        let status = match &entry.last_error {
+            Some(err) => format!("Resumed at {:?} (last error: {}). Press 's' to continue", entry.progress, err),
+            None => format!("Resumed at {:?}. Press 's' to continue", entry.progress),
+        };
+
+        OrderDashboardState {
+            order_id: entry.order_id.unwrap_or_else(|| "Press 's' to create-order".to_string()),
+            status: Some(status),
+            progress: entry.progress,
+            matched_order: entry.matched_order,
+            task: Task::new(),
+            auto: false,
+            step_started: Instant::now(),
+            last_poll: None,
+            secret_input: None,
+            last_bump: None,
+        }
+    }
+
+    /// Dashboard for an order found in the orderbook, continuing from what its swaps show.
+    pub fn open(matched_order: MatchedOrder) -> Self {
+        let progress = infer_progress(&matched_order);
+        let status = format!(
+            "Opened at {:?}. Press 's' to continue or 'r' to refund, redeeming needs the order's secret",
+            progress
+        );
+
+        OrderDashboardState {
+            order_id: matched_order.create_order.create_id.clone().unwrap_or_default(),
+            status: Some(status),
+            progress,
+            matched_order: Some(matched_order),
+            task: Task::new(),
+            auto: false,
+            step_started: Instant::now(),
+            last_poll: None,
+            secret_input: None,
+            last_bump: None,
+        }
+    }
+
+    /// Persists the current progress to the swap journal.
+    fn record(&mut self, context: &AppContext) {
+        let Some(order) = &context.order.current_order else {
+            return;
+        };
+        // orders opened without their secret have nothing worth journaling
+        if !secret_matches(order, &context.order.secret) {
+            return;
+        }
+
+        let mut entry = match context.journal.get(&order.secret_hash) {
+            Ok(Some(entry)) => entry,
+            _ => JournalEntry::new(order.clone(), context.order.secret),
+        };
+        entry.update(&self.order_id, &self.progress, self.matched_order.as_ref());
+
+        if let Err(e) = context.journal.record(entry) {
+            self.set_status(format!("Failed to write swap journal: {}", e));
+        }
+    }
+
+    /// Dispatches the step that follows the current progress to the background task.
+    fn advance(&mut self, context: &AppContext) {
+        let task_context = context.clone();
+        let order_id = self.order_id.clone();
+
+        match self.progress {
+            OrderProgress::NotStarted => {
+                self.task.spawn("Creating order", move || create_order(&task_context));
+            }
+            OrderProgress::OrderCreated => {
+                self.task.spawn("Initiating source swap", move || {
+                    initiate_source(&task_context, &order_id)
+                });
+            }
+            OrderProgress::Initialized => {
+                self.task.spawn("Waiting for destination init", move || {
+                    wait_for_destination(&task_context, &order_id)
+                });
+            }
+            OrderProgress::DestinationInitialized if !secret_matches_context(context) => {
+                self.auto = false;
+                self.secret_input = Some(String::new());
+                self.set_status("The order's secret is needed to redeem, enter it in hex".to_string());
+            }
+            OrderProgress::DestinationInitialized => {
+                self.task.spawn("Redeeming", move || {
+                    redeem_destination(&task_context, &order_id)
+                });
+            }
+            OrderProgress::Redeemed => {
+                self.set_status(
+                    "Order process complete! Press 'c' to start over.".to_string(),
+                );
+            }
+            OrderProgress::Refunded => {
+                self.set_status("Order refunded. Press 'b' to start a new swap.".to_string());
+            }
+            OrderProgress::Failed(ref reason) => {
+                self.set_status(format!("Process failed: {}. Press 'c' to retry.", reason));
+            }
+        }
+    }
+
+    /// Accepts the typed secret once it hashes to the order's secret hash, and journals it.
+    fn submit_secret(&mut self, context: &mut AppContext) {
+        let Some(input) = self.secret_input.clone() else {
+            return;
+        };
+        let secret = match parse_secret(&input) {
+            Ok(secret) => secret,
+            Err(e) => {
+                self.set_status(format!("{:#}", e));
+                return;
+            }
+        };
+
+        let Some(order) = &context.order.current_order else {
+            return;
+        };
+        if !secret_matches(order, &secret) {
+            self.set_status("That secret does not match the order's secret hash".to_string());
+            return;
+        }
+
+        context.order.secret = secret;
+        self.secret_input = None;
+        self.record(context);
+        self.set_status("Secret accepted. Press 's' to redeem".to_string());
+    }
+
+    fn handle_secret_input(&mut self, key: KeyEvent, context: &mut AppContext) {
+        let Some(input) = self.secret_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.secret_input = None;
+                self.clear_error();
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_hexdigit() || c == 'x' => input.push(c),
+            KeyCode::Enter => self.submit_secret(context),
+            _ => {}
+        }
+    }
+
+    fn toggle_auto(&mut self) {
+        self.auto = !self.auto;
+        self.step_started = Instant::now();
+        self.last_poll = None;
+        if self.auto {
+            self.set_status("Auto mode on".to_string());
+        } else {
+            self.set_status("Auto mode off. Press 's' to continue manually".to_string());
+        }
+    }
+
+    // auto mode never moves the progress to `Failed`, so the same step can be retried
+    fn stop_auto(&mut self, reason: String) {
+        self.auto = false;
+        self.set_status(format!(
+            "Auto mode stopped: {}. The secret is kept in the journal, press 'a' to retry or 's' to continue manually",
+            reason
+        ));
+    }
+
+    /// Advances the swap without key presses, polling the orderbook while waiting on the destination.
+    fn drive(&mut self, context: &AppContext) {
+        match self.progress {
+            OrderProgress::Redeemed | OrderProgress::Refunded => {
+                self.auto = false;
+                return;
+            }
+            OrderProgress::Failed(ref reason) => {
+                let reason = reason.clone();
+                self.stop_auto(reason);
+                return;
+            }
+            _ => {}
+        }
+
+        if let Some(timeout) = exceeded_step_timeout(&self.progress, self.step_started.elapsed()) {
+            self.stop_auto(format!(
+                "{:?} timed out after {}s",
+                self.progress,
+                timeout.as_secs()
+            ));
+            return;
+        }
+
+        if self.task.is_running() {
+            return;
+        }
+
+        if self.progress == OrderProgress::Initialized {
+            if self.last_poll.is_some_and(|polled| polled.elapsed() < POLL_INTERVAL) {
+                return;
+            }
+            self.last_poll = Some(Instant::now());
+
+            let task_context = context.clone();
+            let order_id = self.order_id.clone();
+            self.task.spawn("Polling destination", move || {
+                poll_destination(&task_context, &order_id)
+            });
+            return;
+        }
+
+        self.advance(context);
+    }
+
+    /// Spends the source HTLC back to the wallet once its timelock has elapsed.
+    /// Errors leave the progress untouched so the refund can be retried later.
+    fn refund(&mut self, context: &AppContext) {
+        if matches!(
+            self.progress,
+            OrderProgress::NotStarted | OrderProgress::Redeemed | OrderProgress::Refunded
+        ) {
+            self.set_status("Nothing to refund for this order".to_string());
+            return;
+        }
+
+        let task_context = context.clone();
+        let order_id = self.order_id.clone();
+        let progress = self.progress.clone();
+
+        self.task.spawn("Refunding source swap", move || {
+            Ok(match refund_source(&task_context, &order_id, None) {
+                Ok(outcome) => outcome,
+                Err(e) => StepOutcome {
+                    progress,
+                    status: format!("Refund failed: {:#}", e),
+                    order_id: None,
+                    matched_order: None,
+                },
+            })
+        });
+    }
+
+    /// Bitcoin transaction the current step is waiting on, if its fee could be bumped.
+    fn pending_btc_tx(&self) -> Option<BumpTarget> {
+        let matched_order = self.matched_order.as_ref()?;
+        let source = &matched_order.source_swap;
+        match self.progress {
+            OrderProgress::Initialized if source.chain.contains("bitcoin") && source.current_confirmations == 0 => {
+                Some(BumpTarget::Funding)
+            }
+            OrderProgress::Redeemed if matched_order.destination_swap.chain.contains("bitcoin") => {
+                Some(BumpTarget::Redeem)
+            }
+            _ => None,
+        }
+    }
+
+    /// The pending Bitcoin transaction, once it has been waiting long enough to be worth bumping.
+    fn stuck_btc_tx(&self) -> Option<BumpTarget> {
+        let pending_since = self.last_bump.unwrap_or(self.step_started);
+        self.pending_btc_tx().filter(|_| pending_since.elapsed() > STUCK_TX_AFTER)
+    }
+
+    /// Replaces the pending Bitcoin transaction with one paying the current fee priority's rate.
+    /// `bump_fee` refetches the order first and refuses transactions that confirmed in the
+    /// meantime. Errors leave the progress untouched.
+    fn bump(&mut self, context: &AppContext) {
+        let Some(target) = self.pending_btc_tx() else {
+            self.set_status("No Bitcoin transaction of this order is waiting to confirm".to_string());
+            return;
+        };
+
+        let task_context = context.clone();
+        let order_id = self.order_id.clone();
+        let progress = self.progress.clone();
+
+        self.last_bump = Some(Instant::now());
+        self.task.spawn("Bumping fee", move || {
+            let status = match bump_fee(&task_context, &order_id, target) {
+                Ok(tx) => format!("Fee bumped, {:?} replaced by tx {}", target, tx),
+                Err(e) => format!("Fee bump failed: {:#}", e),
+            };
+            // refresh the snapshot the bump is offered from, so a confirmed tx stops showing as stuck
+            let matched_order = task_context.api.orderbook.get_matched_order(&order_id).ok();
+            Ok(StepOutcome {
+                progress,
+                status,
+                order_id: None,
+                matched_order,
+            })
+        });
+    }
+
+    /// Leaves `Failed` for the last successful progress in the journal, or what the order's
+    /// swaps show when it has no entry, so the failed step can be retried.
+    fn restore_progress(&mut self, context: &AppContext) {
+        let journaled = context
+            .order
+            .current_order
+            .as_ref()
+            .and_then(|order| context.journal.get(&order.secret_hash).ok().flatten())
+            .map(|entry| entry.progress);
+
+        self.progress = journaled
+            .or_else(|| self.matched_order.as_ref().map(infer_progress))
+            .unwrap_or(OrderProgress::NotStarted);
+        self.step_started = Instant::now();
+        self.last_poll = None;
+        self.set_status(format!("Back at {:?}. Press 's' to retry", self.progress));
+    }
+
+    fn apply(&mut self, outcome: StepOutcome) {
+        if outcome.progress != self.progress {
+            self.step_started = Instant::now();
+            self.last_bump = None;
+        }
+        self.progress = outcome.progress;
+        self.set_status(outcome.status);
+        if let Some(order_id) = outcome.order_id {
+            self.order_id = order_id;
+        }
+        if outcome.matched_order.is_some() {
+            self.matched_order = outcome.matched_order;
         }
     }
 
@@ -56,7 +390,13 @@ impl State for OrderDashboardState {
     fn draw(&self, frame: &mut Frame, context: &mut AppContext) {
         let size = frame.area();
 
-        let title_span = vec![Span::raw("Order Dashboard")];
+        let mut title_span = vec![Span::raw("Order Dashboard")];
+        if self.auto {
+            title_span.push(Span::styled(
+                format!("  [AUTO {}s]", self.step_started.elapsed().as_secs()),
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            ));
+        }
         let title_line = Line::from(title_span);
         let title_block = Block::default()
             .borders(Borders::ALL)
@@ -70,9 +410,9 @@ impl State for OrderDashboardState {
                 [
                     Constraint::Length(3), // Title
                     Constraint::Length(1),
-                      Constraint::Length(4), //address
+                    Constraint::Length(5), // Addresses and fee priority
                     Constraint::Length(3), // Order ID
-                    Constraint::Length(3), // Status
+                    Constraint::Length(4), // Status
                     Constraint::Min(0),    // Instructions
                 ]
                 .as_ref(),
@@ -91,16 +431,21 @@ impl State for OrderDashboardState {
             .title("Your Addresses")
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::White));
-       
-        let addresses = format!("EVM: {}\nBTC: {}",context.wallet.signer.address().to_string(), context.wallet.btc_address);
-       
+
+        let addresses = format!(
+            "EVM: {}\nBTC: {}\nBTC fee: {}",
+            context.wallet.signer.address(),
+            context.wallet.btc_address,
+            context.fee_priority
+        );
+
         frame.render_widget(
             Paragraph::new(addresses)
                 .block(address_block)
                 .alignment(Alignment::Left),
             chunks[2],
         );
-        
+
         let output_block = Block::default()
             .title("Order ID")
             .borders(Borders::ALL)
@@ -120,10 +465,22 @@ impl State for OrderDashboardState {
             .borders(Borders::ALL)
             .style(error_style);
 
-        let status_message = match &self.status {
-            Some(msg) => msg.clone(),
-            None => "No errors".to_string(),
+        // a running task takes precedence over the last status
+        let mut status_message = match (self.task.progress_line(), &self.status) {
+            (Some(line), _) => line,
+            (None, Some(msg)) => msg.clone(),
+            (None, None) => "No errors".to_string(),
         };
+        if let Some(input) = &self.secret_input {
+            status_message = format!("{}\nSecret: {}  (Enter: Submit | Esc: Cancel)", status_message, input);
+        } else if let Some(target) = self.stuck_btc_tx() {
+            status_message = format!(
+                "{}\nBitcoin {:?} may be unconfirmed after {} minutes, press 'u' to bump its fee",
+                status_message,
+                target,
+                STUCK_TX_AFTER.as_secs() / 60
+            );
+        }
 
         frame.render_widget(
             Paragraph::new(status_message)
@@ -139,6 +496,11 @@ impl State for OrderDashboardState {
                 "b: Back To Strategy Selection | ",
                 Style::default().fg(Color::Green),
             ),
+            Span::styled("a: Auto Mode | ", Style::default().fg(Color::Green)),
+            Span::styled("r: Refund | ", Style::default().fg(Color::Red)),
+            Span::styled("x: Show Secret | ", Style::default().fg(Color::Yellow)),
+            Span::styled("f: Fee Priority | ", Style::default().fg(Color::Yellow)),
+            Span::styled("u: Bump Fee | ", Style::default().fg(Color::Yellow)),
             Span::styled("c: Clear Error", Style::default().fg(Color::Yellow)),
         ];
 
@@ -149,234 +511,61 @@ impl State for OrderDashboardState {
     }
 
     fn handle_key(&mut self, key: KeyEvent, context: &mut AppContext) -> Option<StateType> {
+        if self.secret_input.is_some() {
+            self.handle_secret_input(key, context);
+            return None;
+        }
+
+        // leaving or starting another step while a task runs would drop its result
+        if self.task.is_running() && matches!(key.code, KeyCode::Char('b' | 's' | 'r' | 'u')) {
+            self.set_status("Please wait for the current step to finish".to_string());
+            return None;
+        }
+
         match key.code {
             KeyCode::Char('q') => Some(StateType::Quit),
             KeyCode::Char('b') => Some(StateType::NetworkInformation),
             KeyCode::Char('c') => {
+                if matches!(self.progress, OrderProgress::Failed(_)) {
+                    self.restore_progress(context);
+                } else {
+                    self.clear_error();
+                }
+                None
+            }
+            KeyCode::Char('r') => {
                 self.clear_error();
+                self.auto = false;
+                self.refund(context);
+                None
+            }
+            KeyCode::Char('a') => {
+                self.toggle_auto();
+                None
+            }
+            KeyCode::Char('u') => {
+                self.clear_error();
+                self.bump(context);
+                None
+            }
+            KeyCode::Char('f') => {
+                // steps already running keep the fee rate they started with
+                context.fee_priority = context.fee_priority.next();
+                self.set_status(format!("Bitcoin fee priority: {}", context.fee_priority));
+                None
+            }
+            KeyCode::Char('x') => {
+                if secret_matches_context(context) {
+                    self.set_status(format!("Secret: {}", hex::encode(context.order.secret)));
+                } else {
+                    self.set_status("The secret of this order is not known".to_string());
+                }
                 None
             }
             KeyCode::Char('s') => {
                 // Clear any previous errors
                 self.clear_error();
-
-                match self.progress {
-                    OrderProgress::NotStarted => {
-                        // Create order
-                        let order_result = match &context.order.current_order {
-                            Some(order) => context.api.orderbook.create_order(order.clone()),
-                            None => {
-                                self.set_status("No current order available".to_string());
-                                return None;
-                            }
-                        };
-
-                        // Check if order creation was successful
-                        match order_result {
-                            Ok(order_id) => {
-                                self.order_id = order_id.trim_matches('"').to_string();
-                                self.set_status(
-                                    "Order Created. Press 's' to initialize".to_string(),
-                                );
-                                self.progress = OrderProgress::OrderCreated;
-                                sleep(Duration::from_secs(5));
-                            }
-                            Err(e) => {
-                                self.set_status(format!("Failed to create order: {}", e));
-                                self.progress = OrderProgress::Failed(e.to_string());
-                            }
-                        }
-                    }
-                    OrderProgress::OrderCreated => {
-                        match context.api.orderbook.get_matched_order(&self.order_id) {
-                            Ok(matched_order) => {
-                                let swap = matched_order.source_swap;
-                                if swap.chain.contains("bitcoin"){
-                                    let htlc_handler = HtlcHandler::new(
-                                        context.wallet.btc_network,
-                                        context.wallet.provider_urls.get("bitcoin").unwrap(),
-                                    )
-                                    .unwrap();
-                                    
-    
-                                    let secret_hash_bytes = hex::decode(swap.secret_hash).unwrap();
-    
-                                    let htlc = BitcoinHTLC::new(
-                                        secret_hash_bytes,
-                                        swap.initiator,
-                                        swap.redeemer,
-                                        swap.timelock,
-                                        context.wallet.btc_network,
-                                    )
-                                    .unwrap();
-                                    let amount = big_decimal_to_i64(&swap.amount).unwrap();
-                                    let tx = htlc_handler
-                                        .initaite_htlc(
-                                            context.wallet.btc_private_key,
-                                            htlc.address().expect("failed to get address"),
-                                            amount,
-                                        )
-                                        .unwrap();
-                                    let runtime = tokio::runtime::Runtime::new()
-                                        .map_err(|e| anyhow!("Unable to create runtime: {}", e))
-                                        .unwrap();
-    
-                                    let txid =
-                                        runtime.block_on(htlc_handler.broadcast_tx(&tx)).unwrap();
-    
-                                    self.set_status(format!(
-                                        "Initialized. tx {} Press 's' to wait for destination",
-                                        txid
-                                    ));
-                                    self.progress = OrderProgress::Initialized;
-                                }else {
-                                    let init_data = Initiate::try_from(&swap).unwrap();
-                                    let runtime = tokio::runtime::Runtime::new()
-                                        .map_err(|e| anyhow!("Unable to create runtime: {}", e))
-                                        .unwrap();
-                                    
-                                    let (chain, _) = swap.chain.split_once("_").unwrap();
-                                    let signature = runtime
-                                        .block_on(init_and_get_sig(init_data, &context.wallet.provider_urls[chain], context.wallet.signer.clone(), &swap.asset));
-
-                                    let init_req = InitiateRequest{
-                                        order_id: self.order_id.to_string(),
-                                        signature: signature.to_string(),
-                                        perform_on: "Source".to_string()
-                                    };
-                                    
-                                    let tx = context.api.orderbook.initiate(init_req).unwrap();
-                                    self.set_status(format!(
-                                        "Initialized. tx {} Press 's' to wait for destination",
-                                        tx
-                                    ));
-                                    self.progress = OrderProgress::Initialized;
-                                }
-                                
-                            }
-                            Err(e) => {
-                                self.set_status(format!("Failed to get matched order: {}", e));
-                                self.progress = OrderProgress::Failed(e.to_string());
-                            }
-                        }
-                    }
-                    OrderProgress::Initialized => {
-                        // Wait for destination init
-                        self.set_status("Waiting for destination init...".to_string());
-                        match context
-                            .api
-                            .orderbook
-                            .wait_for_destination_init(&self.order_id)
-                        {
-                            Ok(_) => {
-                                self.set_status(
-                                    "Destination initialized. Press 's' to redeem".to_string(),
-                                );
-                                self.progress = OrderProgress::DestinationInitialized;
-                            }
-                            Err(e) => {
-                                self.set_status(format!("Failed waiting for destination: {}", e));
-                                self.progress = OrderProgress::Failed(e.to_string());
-                            }
-                        }
-                    }
-                    OrderProgress::DestinationInitialized => {
-                        let secret_str = hex::encode(context.order.secret);
-                        if context
-                            .order
-                            .current_order
-                            .as_ref()
-                            .unwrap()
-                            .destination_chain
-                            .contains("bitcoin")
-                        {
-                            let swap = context
-                                .api
-                                .orderbook
-                                .get_matched_order(&self.order_id)
-                                .unwrap()
-                                .destination_swap;
-
-                            let secret_hash_bytes = hex::decode(swap.secret_hash).unwrap();
-
-                            let htlc = BitcoinHTLC::new(
-                                secret_hash_bytes,
-                                swap.initiator,
-                                swap.redeemer,
-                                swap.timelock,
-                                context.wallet.btc_network,
-                            )
-                            .unwrap();
-                            let witness_stack =
-                                htlc.redeem(&context.order.secret.to_vec()).unwrap();
-                            let htlc_handler = HtlcHandler::new(
-                                context.wallet.btc_network,
-                                context.wallet.provider_urls.get("bitcoin").unwrap(),
-                            )
-                            .unwrap();
-
-                            let runtime = tokio::runtime::Runtime::new()
-                                .map_err(|e| anyhow!("Unable to create runtime: {}", e))
-                                .unwrap();
-
-                            let tx = runtime
-                                .block_on(htlc_handler.create_redeem_tx(
-                                    htlc.address().unwrap(),
-                                    witness_stack,
-                                    context.order.current_order.as_ref().unwrap().additional_data.bitcoin_optional_recipient.clone(),
-                                    context.wallet.btc_private_key,
-                                    3,
-                                ))
-                                .unwrap();
-
-                            let tx_hex = serialize_hex(&tx);
-
-                            match context.api.orderbook.btc_redeem(&self.order_id, &tx_hex) {
-                                Ok(tx) if !tx.is_empty() => {
-                                    self.set_status(format!("Redeem Successful!! {} ", tx));
-                                    self.progress = OrderProgress::Redeemed;
-                                }
-                                Ok(_) => {
-                                    self.set_status(
-                                        "Redeem returned empty transaction".to_string(),
-                                    );
-                                    self.progress =
-                                        OrderProgress::Failed("Empty transaction".to_string());
-                                }
-                                Err(e) => {
-                                    self.set_status(format!("Redeem failed: {}", e));
-                                    self.progress = OrderProgress::Failed(e.to_string());
-                                }
-                            }
-                        } else {
-                            match context.api.orderbook.redeem(&self.order_id, &secret_str) {
-                                Ok(tx) if !tx.is_empty() => {
-                                    self.set_status(format!("Redeem Successful!! {} ", tx));
-                                    self.progress = OrderProgress::Redeemed;
-                                }
-                                Ok(_) => {
-                                    self.set_status(
-                                        "Redeem returned empty transaction".to_string(),
-                                    );
-                                    self.progress =
-                                        OrderProgress::Failed("Empty transaction".to_string());
-                                }
-                                Err(e) => {
-                                    self.set_status(format!("Redeem failed: {}", e));
-                                    self.progress = OrderProgress::Failed(e.to_string());
-                                }
-                            }
-                        }
-                    }
-                    OrderProgress::Redeemed => {
-                        self.set_status(
-                            "Order process complete! Press 'c' to start over.".to_string(),
-                        );
-                    }
-                    OrderProgress::Failed(ref reason) => {
-                        self.set_status(format!("Process failed: {}. Press 'c' to retry.", reason));
-                    }
-                }
+                self.advance(context);
                 None
             }
             KeyCode::Enter => None,
@@ -384,4 +573,43 @@ impl State for OrderDashboardState {
             _ => None,
         }
     }
+
+    fn tick(&mut self, context: &mut AppContext) -> Option<StateType> {
+        if let Some(result) = self.task.poll() {
+            match result {
+                Ok(outcome) => self.apply(outcome),
+                Err(e) if self.auto => self.stop_auto(format!("{:#}", e)),
+                // a timeout or server error does not mean the request was dropped, and creating
+                // the order again could open a second one, so only the later steps offer a retry
+                Err(e) if e.downcast_ref::<GardenError>().is_some_and(GardenError::is_transient) => {
+                    if self.progress == OrderProgress::NotStarted {
+                        self.set_status(format!(
+                            "Network error: {:#}. The order may have been created anyway, check the order history before pressing 's' again",
+                            e
+                        ));
+                    } else {
+                        self.set_status(format!("Network error: {:#}. Press 's' to retry", e));
+                    }
+                }
+                Err(e) => {
+                    self.set_status(format!("{:#}", e));
+                    self.progress = OrderProgress::Failed(format!("{:#}", e));
+                }
+            }
+            self.record(context);
+        }
+
+        if self.auto {
+            self.drive(context);
+        }
+        None
+    }
+}
+
+fn secret_matches_context(context: &AppContext) -> bool {
+    context
+        .order
+        .current_order
+        .as_ref()
+        .is_some_and(|order| secret_matches(order, &context.order.secret))
 }
@@ -0,0 +1,139 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout}, style::{Color, Modifier, Style}, text::{Line, Span}, widgets::{Block, Borders, List, ListItem, Paragraph}, Frame
+};
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{context::AppContext, journal::JournalEntry, ui::components::selector::Selector};
+use super::{State, StateType};
+
+pub struct ResumeSwapState {
+    entry_selector: Selector<JournalEntry>,
+    status: Option<String>,
+}
+
+impl ResumeSwapState {
+    pub fn new(entries: Vec<JournalEntry>) -> Self {
+        let items = entries
+            .into_iter()
+            .map(|entry| (entry.secret_hash().to_string(), entry))
+            .collect();
+
+        ResumeSwapState {
+            entry_selector: Selector::from_items(items),
+            status: None,
+        }
+    }
+}
+
+impl State for ResumeSwapState {
+    fn draw(&self, frame: &mut Frame, context: &mut AppContext) {
+        let size = frame.area();
+
+        let title_span = Span::styled(
+            "Unfinished Swaps Found",
+            Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD)
+        );
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(3),   // Title
+                Constraint::Length(3),   // Journal path
+                Constraint::Min(6),      // Entries
+                Constraint::Length(3),   // Status
+                Constraint::Length(1),   // Instructions
+            ].as_ref())
+            .split(size);
+
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(vec![title_span])])
+                .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::White)))
+                .alignment(Alignment::Center),
+            chunks[0],
+        );
+
+        frame.render_widget(
+            Paragraph::new(context.journal.path().display().to_string())
+                .block(Block::default().title("Journal").borders(Borders::ALL))
+                .alignment(Alignment::Left),
+            chunks[1],
+        );
+
+        let items: Vec<ListItem> = self.entry_selector.items
+            .iter()
+            .map(|(_, entry)| {
+                ListItem::new(format!("{} | {} to {} | {:?} | {}",
+                    entry.order_id.as_deref().unwrap_or("not created"),
+                    entry.order.source_chain,
+                    entry.order.destination_chain,
+                    entry.progress,
+                    entry.updated_at))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default().title("Select Swap To Resume").borders(Borders::ALL))
+            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+
+        frame.render_stateful_widget(list, chunks[2], &mut self.entry_selector.state.clone());
+
+        let status_message = match &self.status {
+            Some(msg) => msg.clone(),
+            None => "The secret for each swap is kept in the journal until it is redeemed or refunded".to_string(),
+        };
+
+        frame.render_widget(
+            Paragraph::new(status_message)
+                .block(Block::default().title("Status").borders(Borders::ALL))
+                .alignment(Alignment::Left),
+            chunks[3],
+        );
+
+        let instructions_spans = vec![
+            Span::styled("↑/↓: Navigate | ", Style::default().fg(Color::Red)),
+            Span::styled("Enter: Resume Swap | ", Style::default().fg(Color::Green)),
+            Span::styled("n: New Swap | ", Style::default().fg(Color::Red)),
+            Span::styled("q: Quit", Style::default().fg(Color::Red)),
+        ];
+
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(instructions_spans)])
+                .alignment(Alignment::Center),
+            chunks[4],
+        );
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, context: &mut AppContext) -> Option<StateType> {
+        match key.code {
+            KeyCode::Char('q') => Some(StateType::Quit),
+            KeyCode::Char('n') => Some(StateType::NetworkInformation),
+            KeyCode::Up => {
+                self.entry_selector.previous();
+                None
+            },
+            KeyCode::Down => {
+                self.entry_selector.next();
+                None
+            },
+            KeyCode::Enter => {
+                let entry = self.entry_selector.selected_item()?.1.clone();
+                let secret = match entry.secret_bytes() {
+                    Ok(secret) => secret,
+                    Err(e) => {
+                        self.status = Some(format!("Cannot resume swap: {}", e));
+                        return None;
+                    }
+                };
+
+                context.order.current_strategy = Some(entry.order.additional_data.strategy_id.clone());
+                context.order.current_order = Some(entry.order.clone());
+                context.order.secret = secret;
+
+                Some(StateType::ResumeOrder(Box::new(entry)))
+            },
+            _ => None,
+        }
+    }
+}
@@ -1,22 +1,49 @@
+use bigdecimal::{BigDecimal, FromPrimitive};
+use chrono::{DateTime, Utc};
 use crossterm::event::{KeyEvent, KeyCode};
 use ratatui::widgets::Block;
 use ratatui::widgets::Borders;
+use ratatui::widgets::Clear;
+use ratatui::widgets::List;
+use ratatui::widgets::ListItem;
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 use ratatui::prelude::*;
-
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use crate::context::AppContext;
-use crate::service::garden::quote::generate_secret;
-use crate::service::garden::types::Order;
-use crate::service::garden::types::OrderInputData;
+use crate::journal::JournalEntry;
+use crate::service::garden::types::{parse_quote, Asset, Order, Strategy};
+use crate::swap::build_order;
+use crate::ui::components::selector::Selector;
+use crate::ui::components::task::Task;
 
 use super::{State, StateType};
 
+enum SwapJob {
+    /// Quotes by strategy id for `amount` of the input asset in the given direction.
+    Quote { amount: u64, exact_out: bool, prices: HashMap<String, String> },
+    Attested(Box<Order>, [u8; 32]),
+}
+
 pub struct SwapDashboardState {
     input_value: String,
     input_focused: bool,
-    quote_price: String,
+    /// Every solver's quote for the current input by strategy id, best first, in base units of
+    /// the other side of the swap. The selected one is the current strategy.
+    quotes: Selector<u64>,
+    quoted_at: Option<Instant>,
+    /// Change of the selected quote against the one it replaced, in base units.
+    movement: Option<i128>,
+    last_request: Option<Instant>,
+    refreshing: bool,
+    message: Option<String>,
+    /// The input is the destination amount and the quote the source amount it requires.
+    exact_out: bool,
+    /// Attested order and its secret, waiting for the user to confirm the breakdown.
+    pending: Option<(Order, [u8; 32])>,
+    task: Task<SwapJob>,
 }
 
 impl SwapDashboardState {
@@ -24,7 +51,15 @@ impl SwapDashboardState {
         SwapDashboardState {
             input_value: "".to_string(),
             input_focused: false,
-            quote_price: "".to_string(),
+            quotes: Selector::from_items(Vec::new()),
+            quoted_at: None,
+            movement: None,
+            last_request: None,
+            refreshing: false,
+            message: None,
+            exact_out: false,
+            pending: None,
+            task: Task::new(),
         }
     }
     
@@ -32,16 +67,238 @@ impl SwapDashboardState {
             self.input_focused = !self.input_focused;
         }
         
+    pub fn toggle_exact_out(&mut self) {
+        self.exact_out = !self.exact_out;
+        // a quote for the other direction would be read as the wrong amount
+        self.clear_quote();
+    }
+
+    fn clear_quote(&mut self) {
+        self.quotes = Selector::from_items(Vec::new());
+        self.quoted_at = None;
+        self.movement = None;
+    }
+
     pub fn handle_input(&mut self, key: char) {
-        if self.input_focused {
-            self.input_value.push(key);
+        if !self.input_focused || (key == '.' && self.input_value.contains('.')) {
+            return;
         }
+        self.input_value.push(key);
+        self.clear_quote();
     }
     
     pub fn handle_backspace(&mut self) {
         if self.input_focused && !self.input_value.is_empty() {
             self.input_value.pop();
+            self.clear_quote();
+        }
+    }
+
+    fn quoted(&self) -> Option<u64> {
+        self.quotes.selected_item().map(|(_, quoted)| *quoted)
+    }
+
+    /// Moves the quote selection and makes the newly selected quote's strategy the current one.
+    fn select_quote(&mut self, next: bool, context: &mut AppContext) {
+        if self.quotes.items.is_empty() {
+            return;
+        }
+        if next {
+            self.quotes.next();
+        } else {
+            self.quotes.previous();
+        }
+        self.movement = None;
+        if let Some((strategy_id, _)) = self.quotes.selected_item() {
+            context.order.current_strategy = Some(strategy_id.clone());
+        }
+    }
+
+    /// The asset typed into the input box and the asset the quote is denominated in.
+    fn assets<'a>(&self, strategy: &'a Strategy) -> (&'a Asset, &'a Asset) {
+        if self.exact_out {
+            (&strategy.dest_asset, &strategy.source_asset)
+        } else {
+            (&strategy.source_asset, &strategy.dest_asset)
+        }
+    }
+
+    /// Source and destination amounts in base units for an input and its quote.
+    fn amounts(&self, input: u64, quoted: u64) -> (u64, u64) {
+        if self.exact_out {
+            (quoted, input)
+        } else {
+            (input, quoted)
+        }
+    }
+
+    fn request_quote(&mut self, context: &AppContext, strategy: &Strategy, amount: u64, label: &str) {
+        let order_pair = strategy.order_pair();
+        let quote = context.api.quote.clone();
+        let exact_out = self.exact_out;
+        if self.task.spawn(label, move || {
+            let prices = quote.get_price(&order_pair, &amount.to_string(), exact_out)?;
+            Ok(SwapJob::Quote { amount, exact_out, prices })
+        }) {
+            self.last_request = Some(Instant::now());
+        }
+    }
+
+    /// Re-queries the quote for the current input every `refresh_interval` once one has been fetched.
+    fn refresh_quote(&mut self, context: &AppContext) {
+        if self.quoted().is_none() || self.pending.is_some() || self.task.is_running() {
+            return;
+        }
+        let interval = Duration::from_secs(context.quote_config.refresh_interval);
+        if self.last_request.is_some_and(|requested| requested.elapsed() < interval) {
+            return;
+        }
+
+        let Some(strategy) = context
+            .order
+            .current_strategy
+            .as_ref()
+            .and_then(|id| context.api.quote.strategies_map.get(id))
+        else {
+            return;
+        };
+        if let Ok(amount) = self.input_base_units(strategy) {
+            self.refreshing = true;
+            self.request_quote(context, strategy, amount, "Refreshing quote");
+        }
+    }
+
+    /// Age and movement of the current quote, e.g. `▲ 0.0001  12s ago`.
+    fn quote_freshness(&self, asset: &Asset, max_age: Duration) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        if let Some(movement) = self.movement {
+            let amount = asset.format_amount(&BigDecimal::from(movement.unsigned_abs() as u64));
+            spans.push(match movement {
+                m if m > 0 => Span::styled(format!("  ▲ {}", amount), Style::default().fg(Color::Green)),
+                m if m < 0 => Span::styled(format!("  ▼ {}", amount), Style::default().fg(Color::Red)),
+                _ => Span::raw("  ="),
+            });
+        }
+        if let Some(quoted_at) = self.quoted_at {
+            let age = quoted_at.elapsed();
+            spans.push(if age > max_age {
+                Span::styled(format!("  {}s ago, stale", age.as_secs()), Style::default().fg(Color::Red))
+            } else {
+                Span::styled(format!("  {}s ago", age.as_secs()), Style::default().fg(Color::DarkGray))
+            });
+        }
+        if self.refreshing {
+            spans.push(Span::styled("  refreshing", Style::default().fg(Color::DarkGray)));
+        }
+        spans
+    }
+
+    /// Applies the result of a background job.
+    fn finish(&mut self, result: anyhow::Result<SwapJob>, context: &mut AppContext) -> Option<StateType> {
+        match result {
+            Ok(SwapJob::Quote { amount, exact_out, prices }) => {
+                let strategies = &context.api.quote.strategies_map;
+                let current = context
+                    .order
+                    .current_strategy
+                    .as_ref()
+                    .and_then(|id| strategies.get(id))
+                    .and_then(|strategy| self.input_base_units(strategy).ok());
+                // the input changed while the quote was in flight
+                if exact_out != self.exact_out || current != Some(amount) {
+                    return None;
+                }
+
+                // quotes for strategies this client doesn't know can't be turned into orders
+                let mut quotes: Vec<(String, u64)> = Vec::new();
+                let mut invalid = Vec::new();
+                let known = prices.iter().filter(|(strategy_id, _)| strategies.contains_key(*strategy_id));
+                for (strategy_id, price) in known {
+                    match parse_quote(price) {
+                        Ok(quote) => quotes.push((strategy_id.clone(), quote)),
+                        Err(e) => invalid.push(format!("{}: {}", strategy_id, e)),
+                    }
+                }
+                if quotes.is_empty() {
+                    self.message = Some(if invalid.is_empty() {
+                        format!("no usable quotes in {:?}", prices)
+                    } else {
+                        format!("no usable quotes, {}", invalid.join(", "))
+                    });
+                    return None;
+                }
+                // best first: most received for exact-in, least paid for exact-out
+                quotes.sort_by(|(a_id, a), (b_id, b)| {
+                    let order = if self.exact_out { a.cmp(b) } else { b.cmp(a) };
+                    order.then_with(|| a_id.cmp(b_id))
+                });
+
+                let previous = self.quotes.selected_item().cloned();
+                self.quotes = Selector::from_items(quotes);
+                // a refresh keeps the user's pick instead of jumping to the new best quote
+                if let Some((previous_id, previous_quote)) = previous {
+                    if let Some(index) = self.quotes.items.iter().position(|(id, _)| *id == previous_id) {
+                        self.quotes.state.select(Some(index));
+                        self.movement = Some(self.quotes.items[index].1 as i128 - previous_quote as i128);
+                    } else {
+                        self.movement = None;
+                    }
+                }
+                self.quoted_at = Some(Instant::now());
+                self.message = (!invalid.is_empty()).then(|| format!("ignored {}", invalid.join(", ")));
+
+                let (strategy_id, quoted) = self.quotes.selected_item().cloned()?;
+                // the quote is the source amount in exact-out mode
+                if self.exact_out {
+                    if let Err(e) = strategies[&strategy_id].check_amount(quoted) {
+                        self.message = Some(e);
+                    }
+                }
+                context.order.current_strategy = Some(strategy_id);
+                None
+            }
+            Ok(SwapJob::Attested(attested_order, secret)) => {
+                self.pending = Some((*attested_order, secret));
+                None
+            }
+            Err(e) => {
+                self.message = Some(format!("{:#}", e));
+                None
+            }
+        }
+    }
+
+    /// Journals the confirmed order and hands it over to the order dashboard.
+    fn confirm(&mut self, context: &mut AppContext) -> Option<StateType> {
+        let (attested_order, secret) = self.pending.take()?;
+
+        // the confirmation may have sat on screen long enough for the quote or the attestation to lapse
+        let max_age = Duration::from_secs(context.quote_config.max_age);
+        let quote_expired = self.quoted_at.is_none_or(|quoted_at| quoted_at.elapsed() > max_age);
+        let deadline_passed = attested_order
+            .additional_data
+            .deadline
+            .is_some_and(|deadline| deadline <= Utc::now().timestamp());
+        if quote_expired || deadline_passed {
+            self.message = Some("quote expired, press 'g' to refresh it and 's' to re-create the order".to_string());
+            return None
+        }
+
+        // journal the secret before anything is sent to the orderbook
+        if let Err(e) = context.journal.record(JournalEntry::new(attested_order.clone(), secret)) {
+            self.message = Some(format!("failed to write swap journal: {}", e));
+            return None
+        }
+        context.order.current_order = Some(attested_order);
+        context.order.secret = secret;
+        Some(StateType::OrderInformation)
+    }
+
+    fn input_base_units(&self, strategy: &Strategy) -> Result<u64, String> {
+        if self.input_value.is_empty() {
+            return Err("please enter a valid input amount".to_string());
         }
+        self.assets(strategy).0.to_base_units(&self.input_value)
     }
 }
 impl State for SwapDashboardState {
@@ -85,7 +342,7 @@ impl State for SwapDashboardState {
                 Constraint::Length(4), //address
                 Constraint::Length(1),
                 Constraint::Length(3), //in
-                Constraint::Length(3), // out
+                Constraint::Length((self.quotes.items.len() as u16).clamp(1, 6) + 2), // quotes
                 Constraint::Min(0)
             ].as_ref())
             .split(size);
@@ -103,7 +360,7 @@ impl State for SwapDashboardState {
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::White));
        
-        let addresses = format!("EVM: {}\nBTC: {}",context.wallet.signer.address().to_string(), context.wallet.btc_address);
+        let addresses = format!("EVM: {}\nBTC: {}",context.wallet.signer.address(), context.wallet.btc_address);
        
         frame.render_widget(
             Paragraph::new(addresses)
@@ -113,8 +370,26 @@ impl State for SwapDashboardState {
         );
             
         
+        let strategy = context
+            .order
+            .current_strategy
+            .as_ref()
+            .and_then(|id| context.api.quote.strategies_map.get(id));
+
+        let (input_title, output_title) = match strategy {
+            Some(strategy) if self.exact_out => (
+                format!("Out Amount in {} (exact)", strategy.dest_chain),
+                format!("Required In Amount in {}", strategy.source_chain),
+            ),
+            Some(strategy) => (
+                format!("In Amount in {}", strategy.source_chain),
+                format!("Out Amount in {}", strategy.dest_chain),
+            ),
+            None => ("In Amount".to_string(), "Out Amount".to_string()),
+        };
+
         let input_block = Block::default()
-            .title("In Amount")
+            .title(input_title)
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::White));
         
@@ -125,18 +400,70 @@ impl State for SwapDashboardState {
             chunks[4],
         );
             
-        let output_block = Block::default()
-            .title("Out Amount")
+        let mut output_title = vec![Span::raw(output_title)];
+        let mut warning = None;
+        if let (Some(quoted), Some(strategy)) = (self.quoted(), strategy) {
+            output_title.extend(self.quote_freshness(
+                self.assets(strategy).1,
+                Duration::from_secs(context.quote_config.max_age),
+            ));
+            warning = self.input_base_units(strategy).ok().and_then(|input| {
+                let (in_amount, out_amount) = self.amounts(input, quoted);
+                strategy.price_warning(in_amount, out_amount)
+            });
+        }
+
+        let mut output_block = Block::default()
+            .title(Line::from(output_title))
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::White));
-            
-        
-        frame.render_widget(
-            Paragraph::new(self.quote_price.clone())
-                .block(output_block)
-                .alignment(Alignment::Left),
-            chunks[5],
-        );
+        if let Some(warning) = warning {
+            output_block = output_block.title_bottom(Line::styled(
+                format!("warning: {}", warning),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+
+        match (self.task.progress_line(), &self.message) {
+            // background refreshes keep showing the current quotes
+            (Some(line), _) if !self.refreshing => {
+                frame.render_widget(Paragraph::new(line).block(output_block), chunks[5]);
+            }
+            (_, Some(message)) => {
+                frame.render_widget(
+                    Paragraph::new(Line::styled(message.clone(), Style::default().fg(Color::Red)))
+                        .block(output_block),
+                    chunks[5],
+                );
+            }
+            _ => {
+                let strategies = &context.api.quote.strategies_map;
+                let input = strategy.and_then(|strategy| self.input_base_units(strategy).ok());
+                let items: Vec<ListItem> = self.quotes.items
+                    .iter()
+                    .filter_map(|(id, quoted)| {
+                        let quote_strategy = strategies.get(id)?;
+                        let (in_amount, out_amount) = self.amounts(input?, *quoted);
+                        Some(ListItem::new(format!(
+                            "{:<16} {}  rate {:.8}  fee {} bips  {} confs  maker {}",
+                            self.assets(quote_strategy).1.format_amount(&(*quoted).into()),
+                            id,
+                            quote_strategy.rate(&in_amount.into(), &out_amount.into()),
+                            quote_strategy.fee,
+                            quote_strategy.min_source_confirmations,
+                            quote_strategy.makers.join(", ")
+                        )))
+                    })
+                    .collect();
+
+                let list = List::new(items)
+                    .block(output_block)
+                    .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                    .highlight_symbol("> ");
+
+                frame.render_stateful_widget(list, chunks[5], &mut self.quotes.state.clone());
+            }
+        }
             
         if self.input_focused {
             frame.set_cursor_position(
@@ -148,7 +475,9 @@ impl State for SwapDashboardState {
             Span::styled("q: Quit | ", Style::default().fg(Color::Red)),
             Span::styled("b: Strategy Selection | ", Style::default().fg(Color::Red)),
             Span::styled("g: Get Quote | ", Style::default().fg(Color::Red)),
+            Span::styled("↑/↓: Pick Quote | ", Style::default().fg(Color::Yellow)),
             Span::styled("s: SWAP | ", Style::default().fg(Color::Green)),
+            Span::styled("e: Toggle Exact Out | ", Style::default().fg(Color::Yellow)),
             Span::styled("i: Toggle Input Focus", Style::default().fg(Color::Red)),
         ];
             
@@ -157,9 +486,52 @@ impl State for SwapDashboardState {
                 .alignment(Alignment::Center),
             chunks[6],
         );
+
+        if let (Some((order, _)), Some(strategy)) = (&self.pending, strategy) {
+            let mut lines = confirmation_lines(order, strategy);
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled("y/Enter: Confirm | ", Style::default().fg(Color::Green)),
+                Span::styled("n/Esc: Cancel", Style::default().fg(Color::Red)),
+            ]).alignment(Alignment::Center));
+
+            let width = size.width.min(72);
+            let height = (lines.len() as u16 + 2).min(size.height);
+            let area = Rect::new(
+                size.x + (size.width - width) / 2,
+                size.y + (size.height - height) / 2,
+                width,
+                height,
+            );
+            let confirm_block = Block::default()
+                .title("Confirm Swap")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::White));
+
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(lines).block(confirm_block), area);
+        }
     }
     
     fn handle_key(&mut self, key: KeyEvent, context: &mut AppContext) -> Option<StateType> {
+        if self.pending.is_some() {
+            return match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => self.confirm(context),
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.pending = None;
+                    self.message = Some("Swap cancelled".to_string());
+                    None
+                }
+                KeyCode::Char('q') => Some(StateType::Quit),
+                _ => None,
+            };
+        }
+
+        // the attested order carries the secret hash, so don't leave before it has been journaled
+        if self.task.is_running() && matches!(key.code, KeyCode::Char('b' | 's' | 'g' | 'e')) {
+            return None;
+        }
+
         match key.code {
             KeyCode::Char('q') => Some(StateType::Quit),
             KeyCode::Char('b') => Some(StateType::NetworkInformation), 
@@ -167,64 +539,89 @@ impl State for SwapDashboardState {
                 self.toggle_input_focus();
                 None
             },
+            KeyCode::Char('e') => {
+                self.toggle_exact_out();
+                None
+            },
+            KeyCode::Up => {
+                self.select_quote(false, context);
+                None
+            },
+            KeyCode::Down => {
+                self.select_quote(true, context);
+                None
+            },
             KeyCode::Char('s') => {
-                if self.quote_price.is_empty() || self.input_value.is_empty(){
-                    self.quote_price = "please ensure to get quote price".to_string();
+                let strategy = context
+                    .order
+                    .current_strategy
+                    .as_ref()
+                    .and_then(|id| context.api.quote.strategies_map.get(id))?;
+                let (input, quoted) = match (self.input_base_units(strategy), self.quoted()) {
+                    (Ok(input), Some(quoted)) => (input, quoted),
+                    (Err(e), _) => {
+                        self.message = Some(e);
+                        return None
+                    }
+                    (Ok(_), None) => {
+                        self.message = Some("please ensure to get quote price".to_string());
+                        return None
+                    }
+                };
+                let max_age = Duration::from_secs(context.quote_config.max_age);
+                if let Some(age) = self.quoted_at.map(|quoted_at| quoted_at.elapsed()).filter(|age| *age > max_age) {
+                    self.message = Some(format!(
+                        "quote is {}s old, older than the {}s limit. Press 'g' to refresh",
+                        age.as_secs(),
+                        max_age.as_secs()
+                    ));
                     return None
                 }
-                if let Some(current_strategy) = &context.order.current_strategy {
-                    if let Some(strategy) = context.api.quote.strategies_map.get(current_strategy) {
-                        let in_amount = self.input_value.parse::<u64>().unwrap();
-                        let out_amount = self.quote_price.parse::<u64>().unwrap();
-                        let (init_src_add, init_dest_addr, btc_opt_recp ) = if strategy.source_chain.contains("bitcoin") {
-                            (context.wallet.btc_xpubkey.to_string(), context.wallet.signer.address().to_string(), None)
-                        } else if strategy.dest_chain.contains("bitcoin") {
-                            
-                            (context.wallet.signer.address().to_string(), context.wallet.btc_xpubkey.to_string(), Some(context.wallet.btc_address.clone()))
-                        } else {
-                            (context.wallet.signer.address().to_string(), context.wallet.signer.address().to_string(), None)
-                        };
-                        
-                        let (secret, secret_hash) = generate_secret().unwrap();
-                        let _order = Order::new(OrderInputData{
-                            initiator_source_address: init_src_add,
-                            initiator_dest_address: init_dest_addr,
-                            in_amount,
-                            out_amount, 
-                            secret_hash: hex::encode(secret_hash),
-                            strategy: strategy.clone(),
-                            btc_opt_recipient: btc_opt_recp
-                        });
-                        
-                        
-                        let attested_order = context.api.quote.get_attested_quote(_order).expect("error getting attested quote");
-                        context.order.current_order = Some(attested_order);
-                        context.order.secret = secret
-                    }
+                let (in_amount, out_amount) = self.amounts(input, quoted);
+                if let Err(e) = strategy.check_amount(in_amount) {
+                    self.message = Some(e);
+                    return None
                 }
-                Some(StateType::OrderInformation)
+                let (_order, secret) = match build_order(context, strategy, in_amount, out_amount) {
+                    Ok(built) => built,
+                    Err(e) => {
+                        self.message = Some(format!("{:#}", e));
+                        return None
+                    }
+                };
+
+                let quote = context.api.quote.clone();
+                self.task.spawn("Getting attested quote", move || {
+                    let attested_order = quote.get_attested_quote(_order)?;
+                    Ok(SwapJob::Attested(Box::new(attested_order), secret))
+                });
+                None
             },
             KeyCode::Char('g') => {
-                if self.input_value.is_empty(){
-                    self.quote_price = "please enter a valid input amount".to_string();
-                    return None
-                }
-                if let Some(strategy) = &context.order.current_strategy {
-                    let details = match context.api.quote.strategies_map.get(strategy) {
-                        Some(details) => details,
-                        None => return None,
-                    };
-                   
-                    let order_pair = format!("{}:{}::{}:{}", details.source_chain, details.source_asset.asset, details.dest_chain, details.dest_asset.asset);
-                    if let Ok(price) = context.api.quote.get_price(&order_pair, &self.input_value) {
-                        self.quote_price = price.trim_matches('"').to_string();
+                let details = context
+                    .order
+                    .current_strategy
+                    .as_ref()
+                    .and_then(|id| context.api.quote.strategies_map.get(id))?;
+                // in exact-out mode the source amount is only known once quoted
+                let amount = match self.input_base_units(details).and_then(|amount| {
+                    if !self.exact_out {
+                        details.check_amount(amount)?;
+                    }
+                    Ok(amount)
+                }) {
+                    Ok(amount) => amount,
+                    Err(e) => {
+                        self.message = Some(e);
                         return None
                     }
-                }
+                };
+                self.message = None;
+                self.request_quote(context, details, amount, "Fetching quote");
                 None
             }
             KeyCode::Char(c) => {
-                if c.is_ascii_digit(){
+                if c.is_ascii_digit() || c == '.' {
                     self.handle_input(c);
                 }
                 None
@@ -236,4 +633,59 @@ impl State for SwapDashboardState {
             _ => None,
         }
     }
-}
\ No newline at end of file
+
+    fn tick(&mut self, context: &mut AppContext) -> Option<StateType> {
+        if let Some(result) = self.task.poll() {
+            self.refreshing = false;
+            return self.finish(result, context);
+        }
+        self.refresh_quote(context);
+        None
+    }
+}
+
+/// Rate, fee, USD values, timelock and deadline of an attested order.
+fn confirmation_lines(order: &Order, strategy: &Strategy) -> Vec<Line<'static>> {
+    let source = &strategy.source_asset;
+    let dest = &strategy.dest_asset;
+    let usd = |price: Option<f64>, asset: &Asset, amount: &BigDecimal| {
+        match price.and_then(BigDecimal::from_f64) {
+            Some(price) => format!("(${:.2})", price * asset.to_whole_units(amount)),
+            None => "(USD value unavailable)".to_string(),
+        }
+    };
+    let rate = strategy.rate(&order.source_amount, &order.destination_amount);
+    let fee = &order.source_amount * &order.fee / BigDecimal::from(10_000);
+    let deadline = order
+        .additional_data
+        .deadline
+        .and_then(|deadline| DateTime::from_timestamp(deadline, 0))
+        .map(|deadline| deadline.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "none".to_string());
+
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<12}", label), Style::default().fg(Color::Yellow)),
+            Span::raw(value),
+        ])
+    };
+
+    vec![
+        row("You send", format!(
+            "{} on {} {}",
+            source.format_amount(&order.source_amount),
+            strategy.source_chain,
+            usd(order.additional_data.input_token_price, source, &order.source_amount)
+        )),
+        row("You receive", format!(
+            "{} on {} {}",
+            dest.format_amount(&order.destination_amount),
+            strategy.dest_chain,
+            usd(order.additional_data.output_token_price, dest, &order.destination_amount)
+        )),
+        row("Rate", format!("1 {} = {:.8} {}", source.token_id, rate, dest.token_id)),
+        row("Fee", format!("{} bips ({} on {})", order.fee, source.format_amount(&fee), strategy.source_chain)),
+        row("Timelock", format!("{} blocks on {}", order.timelock, strategy.source_chain)),
+        row("Deadline", deadline),
+    ]
+}